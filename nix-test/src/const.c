@@ -306,6 +306,17 @@ get_int_const(const char* err) {
     // GET_CONST(SO_DOMAIN);
     // GET_CONST(SO_MARK);
     GET_CONST(TCP_CORK);
+    GET_CONST(TCP_KEEPINTVL);
+    GET_CONST(TCP_KEEPCNT);
+    GET_CONST(TCP_INFO);
+    GET_CONST(TCP_QUICKACK);
+    GET_CONST(TCP_CONGESTION);
+    GET_CONST(IP_TOS);
+    GET_CONST(IP_FREEBIND);
+    GET_CONST(IP_TRANSPARENT);
+    GET_CONST(IPV6_V6ONLY);
+    GET_CONST(SO_ATTACH_FILTER);
+    GET_CONST(SO_DETACH_FILTER);
     // GET_CONST(SO_BUSY_POLL);
     // GET_CONST(SO_RXQ_OVFL);
     GET_CONST(SO_PASSCRED);
@@ -0,0 +1,168 @@
+//! List directory contents through a file descriptor.
+//!
+//! Unlike `std::fs::read_dir`, a `Dir` can be opened relative to another
+//! directory with `openat`, and its own file descriptor can be used with the
+//! `*at` functions, so a tree can be walked without resolving any path
+//! twice.
+
+use {Error, Errno, NixPath, Result};
+use fcntl::{self, OFlag, O_DIRECTORY};
+use libc;
+use std::ffi::CStr;
+use std::os::unix::io::{AsRawFd, RawFd};
+use sys::stat::Mode;
+use unistd;
+
+/// An open directory stream, closed when it is dropped.
+///
+/// # Example
+///
+/// ```
+/// use nix::dir::Dir;
+/// use nix::fcntl::O_RDONLY;
+/// use nix::sys::stat::Mode;
+///
+/// let mut dir = Dir::open("/", O_RDONLY, Mode::empty()).unwrap();
+/// for entry in dir.iter() {
+///     let entry = entry.unwrap();
+///     println!("{:?} {:?}", entry.file_name(), entry.file_type());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Dir(*mut libc::DIR);
+
+// The stream isn't safe to use from several threads at once, but it can be
+// moved between them.
+unsafe impl Send for Dir {}
+
+impl Dir {
+    /// Open the directory at `path`.  `O_DIRECTORY` is added to `oflag`.
+    pub fn open<P: ?Sized + NixPath>(path: &P, oflag: OFlag, mode: Mode) -> Result<Dir> {
+        let fd = try!(fcntl::open(path, oflag | O_DIRECTORY, mode));
+        Dir::from_fd(fd)
+    }
+
+    /// Open the directory at `path` relative to the directory `dirfd`.
+    /// `O_DIRECTORY` is added to `oflag`.
+    pub fn openat<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, oflag: OFlag, mode: Mode) -> Result<Dir> {
+        let fd = try!(fcntl::openat(dirfd, path, oflag | O_DIRECTORY, mode));
+        Dir::from_fd(fd)
+    }
+
+    /// Take ownership of the open directory `fd`.  The file descriptor is
+    /// closed when the `Dir` is dropped, or right away if this fails.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man3/fdopendir.3.html)
+    pub fn from_fd(fd: RawFd) -> Result<Dir> {
+        let dirp = unsafe { libc::fdopendir(fd) };
+        if dirp.is_null() {
+            let e = Error::last();
+            let _ = unistd::close(fd);
+            return Err(e);
+        }
+        Ok(Dir(dirp))
+    }
+
+    /// Iterate over the entries, including `.` and `..`, continuing where
+    /// the last iteration stopped.
+    pub fn iter(&mut self) -> Iter {
+        Iter(self)
+    }
+
+    /// Restart from the first entry.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man3/rewinddir.3.html)
+    pub fn rewind(&mut self) {
+        unsafe { libc::rewinddir(self.0) }
+    }
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        unsafe { libc::dirfd(self.0) }
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        unsafe { libc::closedir(self.0) };
+    }
+}
+
+/// An iterator over the entries of a `Dir`.
+#[derive(Debug)]
+pub struct Iter<'d>(&'d mut Dir);
+
+impl<'d> Iterator for Iter<'d> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Result<Entry>> {
+        unsafe {
+            // readdir returns null both at the end and on errors, which only
+            // errno tells apart.
+            Errno::clear();
+            let ent = libc::readdir((self.0).0);
+            if ent.is_null() {
+                match Errno::last() {
+                    Errno::UnknownErrno => None,
+                    errno => Some(Err(Error::Sys(errno))),
+                }
+            } else {
+                Some(Ok(Entry(*ent)))
+            }
+        }
+    }
+}
+
+/// A directory entry, as returned by `Iter`.
+#[derive(Copy, Clone)]
+pub struct Entry(libc::dirent);
+
+/// The type of a directory entry.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Type {
+    Fifo,
+    CharacterDevice,
+    Directory,
+    BlockDevice,
+    File,
+    Symlink,
+    Socket,
+}
+
+impl Entry {
+    /// The inode number of the entry.
+    pub fn ino(&self) -> u64 {
+        self.0.d_ino as u64
+    }
+
+    /// The name of the entry, relative to the directory.
+    pub fn file_name(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.0.d_name.as_ptr()) }
+    }
+
+    /// The type of the entry, if the file system reports it.  When it
+    /// doesn't, use `fstatat` on the name.
+    pub fn file_type(&self) -> Option<Type> {
+        match self.0.d_type {
+            libc::DT_FIFO => Some(Type::Fifo),
+            libc::DT_CHR => Some(Type::CharacterDevice),
+            libc::DT_DIR => Some(Type::Directory),
+            libc::DT_BLK => Some(Type::BlockDevice),
+            libc::DT_REG => Some(Type::File),
+            libc::DT_LNK => Some(Type::Symlink),
+            libc::DT_SOCK => Some(Type::Socket),
+            _ => None,
+        }
+    }
+}
+
+impl ::std::fmt::Debug for Entry {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("ino", &self.ino())
+            .field("file_name", &self.file_name())
+            .field("file_type", &self.file_type())
+            .finish()
+    }
+}
@@ -1,4 +1,4 @@
-use {Error, Errno, Result, NixPath};
+use {Error, Errno, Result, NixPath, at_rawfd};
 use libc::{self, c_int, c_uint, c_char, size_t, ssize_t};
 use sys::stat::Mode;
 use std::os::unix::io::RawFd;
@@ -25,6 +25,10 @@ libc_bitflags!{
     pub flags AtFlags: c_int {
         AT_SYMLINK_NOFOLLOW,
         #[cfg(any(target_os = "linux", target_os = "android"))]
+        AT_SYMLINK_FOLLOW,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        AT_REMOVEDIR,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
         AT_NO_AUTOMOUNT,
         #[cfg(any(target_os = "linux", target_os = "android"))]
         AT_EMPTY_PATH
@@ -85,6 +89,55 @@ pub fn readlinkat<'a, P: ?Sized + NixPath>(dirfd: RawFd, path: &P, buffer: &'a m
     wrap_readlink_result(buffer, res)
 }
 
+/// Rename `oldpath` relative to the directory `olddirfd` to `newpath`
+/// relative to `newdirfd`.  `None` stands for the current working directory.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/renameat.2.html)
+pub fn renameat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(olddirfd: Option<RawFd>, oldpath: &P1,
+                                                          newdirfd: Option<RawFd>, newpath: &P2)
+                                                          -> Result<()> {
+    let res = try!(try!(oldpath.with_nix_path(|old| {
+        newpath.with_nix_path(|new| unsafe {
+            libc::renameat(at_rawfd(olddirfd), old.as_ptr(), at_rawfd(newdirfd), new.as_ptr())
+        })
+    })));
+    Errno::result(res).map(drop)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+bitflags!(
+    /// Flags for `renameat2`
+    pub flags RenameFlags: c_uint {
+        /// Fail with `EEXIST` instead of replacing `newpath`.
+        const RENAME_NOREPLACE = 1,
+        /// Atomically swap `oldpath` and `newpath`, which both have to exist.
+        const RENAME_EXCHANGE = 2,
+        /// Leave an overlay/union whiteout at `oldpath`.
+        const RENAME_WHITEOUT = 4,
+    }
+);
+
+/// Like `renameat`, with `flags` to refuse replacing the target or to swap
+/// both files atomically.  Needs Linux 3.15 and a file system supporting
+/// the flags, otherwise it fails with `EINVAL`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/rename.2.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn renameat2<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(olddirfd: Option<RawFd>, oldpath: &P1,
+                                                           newdirfd: Option<RawFd>, newpath: &P2,
+                                                           flags: RenameFlags)
+                                                           -> Result<()> {
+    use sys::syscall::{syscall, RENAMEAT2};
+    // There is no libc wrapper
+    let res = try!(try!(oldpath.with_nix_path(|old| {
+        newpath.with_nix_path(|new| unsafe {
+            syscall(RENAMEAT2, at_rawfd(olddirfd), old.as_ptr(),
+                    at_rawfd(newdirfd), new.as_ptr(), flags.bits())
+        })
+    })));
+    Errno::result(res).map(drop)
+}
+
 pub enum FcntlArg<'a> {
     F_DUPFD(RawFd),
     F_DUPFD_CLOEXEC(RawFd),
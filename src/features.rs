@@ -8,12 +8,14 @@ mod os {
     // * atomic cloexec on socket: 2.6.27
     // * pipe2: 2.6.27
     // * accept4: 2.6.28
+    // * statx: 4.11
 
     static VERS_UNKNOWN: usize = 1;
     static VERS_2_6_18:  usize = 2;
     static VERS_2_6_27:  usize = 3;
     static VERS_2_6_28:  usize = 4;
     static VERS_3:       usize = 5;
+    static VERS_4_11:    usize = 6;
 
     #[inline]
     fn digit(dst: &mut usize, b: u8) {
@@ -49,7 +51,9 @@ mod os {
             }
         }
 
-        if major >= 3 {
+        if major >= 5 || (major == 4 && minor >= 11) {
+            VERS_4_11
+        } else if major >= 3 {
             VERS_3
         } else if major >= 2 {
             if minor >= 7 {
@@ -86,6 +90,10 @@ mod os {
         kernel_version() >= VERS_2_6_27
     }
 
+    pub fn statx_available() -> bool {
+        kernel_version() >= VERS_4_11
+    }
+
     #[test]
     pub fn test_parsing_kernel_version() {
         assert!(kernel_version() > 0);
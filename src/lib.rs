@@ -34,6 +34,7 @@ pub mod libc {
 pub use libc::{c_int, c_void};
 pub use errno::Errno;
 
+pub mod dir;
 pub mod errno;
 pub mod features;
 pub mod fcntl;
@@ -49,13 +50,15 @@ pub mod pty;
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 pub mod poll;
 
-pub mod net;
-
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub mod sched;
 
+#[macro_use]
 pub mod sys;
 
+// Uses the ioctl! macro from sys, so it has to come after it.
+pub mod net;
+
 // This can be implemented for other platforms as soon as libc
 // provides bindings for them.
 #[cfg(all(target_os = "linux",
@@ -270,3 +273,9 @@ impl<'a, NP: ?Sized + NixPath>  NixPath for Option<&'a NP> {
         }
     }
 }
+
+/// Turns the optional directory of the `*at` functions into a file
+/// descriptor, with `None` meaning the current working directory.
+fn at_rawfd(fd: Option<std::os::unix::io::RawFd>) -> libc::c_int {
+    fd.unwrap_or(libc::AT_FDCWD)
+}
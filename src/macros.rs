@@ -1,6 +1,7 @@
 /// The `libc_bitflags!` macro helps with a common use case of defining bitflags with values from
 /// the libc crate. It is used the same way as the `bitflags!` macro, except that only the name of
-/// the flag value has to be given.
+/// the flag value has to be given.  A flag whose libc constant has another type than the flags
+/// can be converted with `as`, e.g. `IFF_UP as c_uint`.
 ///
 /// The `libc` crate must be in scope with the name `libc`.
 ///
@@ -147,6 +148,39 @@ macro_rules! libc_bitflags {
         }
     };
 
+    // Munch last ident with a cast if not followed by a comma.
+    (@accumulate_flags
+        $prefix:tt,
+        [$($flags:tt)*];
+        $flag:ident as $cast:ty
+    ) => {
+        libc_bitflags! {
+            @accumulate_flags
+            $prefix,
+            [
+                $($flags)*
+                const $flag = libc::$flag as $cast,
+            ];
+        }
+    };
+
+    // Munch an ident with a cast; covers terminating comma case.
+    (@accumulate_flags
+        $prefix:tt,
+        [$($flags:tt)*];
+        $flag:ident as $cast:ty, $($tail:tt)*
+    ) => {
+        libc_bitflags! {
+            @accumulate_flags
+            $prefix,
+            [
+                $($flags)*
+                const $flag = libc::$flag as $cast,
+            ];
+            $($tail)*
+        }
+    };
+
     // (non-pub) Entry rule.
     (
         $(#[$attr:meta])*
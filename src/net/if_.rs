@@ -1,11 +1,97 @@
 //! Network interface name resolution.
 //!
 //! Uses Linux and/or POSIX functions to resolve interface names like "eth0"
-//! or "socan1" into device numbers.
+//! or "socan1" into device numbers, and to enumerate interfaces and their
+//! addresses.  On Linux, interfaces can also be configured with the
+//! `SIOCGIF*`/`SIOCSIF*` ioctls on a control socket.
 
 use libc;
-use libc::c_uint;
-use {Result, Error, NixPath};
+use libc::{c_char, c_int, c_uint, sockaddr};
+use std::{mem, ptr};
+use std::ffi::CStr;
+use {Errno, Result, Error, NixPath};
+use sys::socket::{self, SockAddr};
+
+/// The maximum length of an interface name, including the terminating null.
+pub const IF_NAMESIZE: usize = 16;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+libc_bitflags!{
+    /// Standard interface flags, used by `getifaddrs`.  Their type is that
+    /// of the `ifa_flags` member of `ifaddrs`.
+    pub flags InterfaceFlags: c_uint {
+        /// Interface is running.
+        IFF_UP as c_uint,
+        /// Valid broadcast address set.
+        IFF_BROADCAST as c_uint,
+        /// Internal debugging flag.
+        IFF_DEBUG as c_uint,
+        /// Interface is a loopback interface.
+        IFF_LOOPBACK as c_uint,
+        /// Interface is a point-to-point link.
+        IFF_POINTOPOINT as c_uint,
+        /// Avoid use of trailers.
+        IFF_NOTRAILERS as c_uint,
+        /// Resources allocated.
+        IFF_RUNNING as c_uint,
+        /// No arp protocol, L2 destination address not set.
+        IFF_NOARP as c_uint,
+        /// Interface is in promiscuous mode.
+        IFF_PROMISC as c_uint,
+        /// Receive all multicast packets.
+        IFF_ALLMULTI as c_uint,
+        /// Master of a load balancing bundle.
+        IFF_MASTER as c_uint,
+        /// Slave of a load balancing bundle.
+        IFF_SLAVE as c_uint,
+        /// Supports multicast.
+        IFF_MULTICAST as c_uint,
+        /// Is able to select media type via ifmap.
+        IFF_PORTSEL as c_uint,
+        /// Auto media selection active.
+        IFF_AUTOMEDIA as c_uint,
+        /// The addresses are lost when the interface goes down.
+        IFF_DYNAMIC as c_uint,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+libc_bitflags!{
+    /// Standard interface flags, used by `getifaddrs`.  Their type is that
+    /// of the `ifa_flags` member of `ifaddrs`.
+    pub flags InterfaceFlags: c_uint {
+        /// Interface is running.
+        IFF_UP as c_uint,
+        /// Valid broadcast address set.
+        IFF_BROADCAST as c_uint,
+        /// Internal debugging flag.
+        IFF_DEBUG as c_uint,
+        /// Interface is a loopback interface.
+        IFF_LOOPBACK as c_uint,
+        /// Interface is a point-to-point link.
+        IFF_POINTOPOINT as c_uint,
+        /// Resources allocated.
+        IFF_RUNNING as c_uint,
+        /// No arp protocol, L2 destination address not set.
+        IFF_NOARP as c_uint,
+        /// Interface is in promiscuous mode.
+        IFF_PROMISC as c_uint,
+        /// Receive all multicast packets.
+        IFF_ALLMULTI as c_uint,
+        /// Transmission in progress.
+        IFF_OACTIVE as c_uint,
+        /// Can't hear own transmissions.
+        IFF_SIMPLEX as c_uint,
+        /// Per link layer defined bit.
+        IFF_LINK0 as c_uint,
+        /// Per link layer defined bit.
+        IFF_LINK1 as c_uint,
+        /// Per link layer defined bit.
+        IFF_LINK2 as c_uint,
+        /// Supports multicast.
+        IFF_MULTICAST as c_uint,
+    }
+}
 
 /// Resolve an interface into a interface number.
 pub fn if_nametoindex<P: ?Sized + NixPath>(name: &P) -> Result<c_uint> {
@@ -17,3 +103,427 @@ pub fn if_nametoindex<P: ?Sized + NixPath>(name: &P) -> Result<c_uint> {
         Ok(if_index)
     }
 }
+
+/// Resolve an interface number into the name of the interface.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man3/if_indextoname.3.html)
+pub fn if_indextoname(index: c_uint) -> Result<String> {
+    let mut buf = [0 as c_char; IF_NAMESIZE];
+    let res = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if res.is_null() {
+        return Err(Error::last());
+    }
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+/// An interface index and name, as returned by `if_nameindex`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interface {
+    pub index: c_uint,
+    pub name: String,
+}
+
+/// List the indexes and names of all network interfaces.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man3/if_nameindex.3.html)
+pub fn if_nameindex() -> Result<Vec<Interface>> {
+    unsafe {
+        let base = libc::if_nameindex();
+        if base.is_null() {
+            return Err(Error::last());
+        }
+        let mut interfaces = Vec::new();
+        let mut ifn = base;
+        while (*ifn).if_index != 0 {
+            interfaces.push(Interface {
+                index: (*ifn).if_index,
+                name: CStr::from_ptr((*ifn).if_name).to_string_lossy().into_owned(),
+            });
+            ifn = ifn.offset(1);
+        }
+        libc::if_freenameindex(base);
+        Ok(interfaces)
+    }
+}
+
+/// An address of a network interface, as returned by `getifaddrs`.  An
+/// interface with several addresses appears once per address, and on some
+/// platforms once more for its link-layer address.
+#[derive(Clone)]
+pub struct InterfaceAddress {
+    /// Name of the network interface
+    pub interface_name: String,
+    /// Flags as from `SIOCGIFFLAGS` ioctl
+    pub flags: InterfaceFlags,
+    /// Network address of this interface
+    pub address: Option<SockAddr>,
+    /// Netmask of this interface
+    pub netmask: Option<SockAddr>,
+    /// Broadcast address of this interface, if applicable
+    pub broadcast: Option<SockAddr>,
+    /// Point-to-point destination address
+    pub destination: Option<SockAddr>,
+}
+
+// Convert a sockaddr of a family SockAddr knows about.  getifaddrs doesn't
+// return lengths, so they are implied by the family.
+unsafe fn to_sock_addr(sa: *const sockaddr) -> Option<SockAddr> {
+    if sa.is_null() {
+        return None;
+    }
+    let len = match (*sa).sa_family as c_int {
+        socket::AF_INET => mem::size_of::<socket::sockaddr_in>(),
+        socket::AF_INET6 => mem::size_of::<socket::sockaddr_in6>(),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        socket::AF_PACKET => mem::size_of::<socket::sockaddr_ll>(),
+        _ => return None,
+    };
+    let mut ss: socket::sockaddr_storage = mem::zeroed();
+    ptr::copy_nonoverlapping(sa as *const u8, &mut ss as *mut _ as *mut u8, len);
+    socket::sockaddr_storage_to_addr(&ss, len).ok()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn ifa_ifu(ifa: &libc::ifaddrs) -> *const sockaddr {
+    ifa.ifa_ifu
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn ifa_ifu(ifa: &libc::ifaddrs) -> *const sockaddr {
+    ifa.ifa_dstaddr
+}
+
+impl InterfaceAddress {
+    unsafe fn from_ifaddrs(ifa: &libc::ifaddrs) -> InterfaceAddress {
+        let flags = InterfaceFlags::from_bits_truncate(ifa.ifa_flags);
+        // The broadcast address if IFF_BROADCAST is set, the destination
+        // address if IFF_POINTOPOINT is set
+        let ifu = to_sock_addr(ifa_ifu(ifa));
+        InterfaceAddress {
+            interface_name: CStr::from_ptr(ifa.ifa_name).to_string_lossy().into_owned(),
+            flags: flags,
+            address: to_sock_addr(ifa.ifa_addr),
+            netmask: to_sock_addr(ifa.ifa_netmask),
+            broadcast: if flags.contains(IFF_BROADCAST) { ifu } else { None },
+            destination: if flags.contains(IFF_POINTOPOINT) { ifu } else { None },
+        }
+    }
+}
+
+/// An iterator over the addresses returned by `getifaddrs`, which frees
+/// them when dropped.
+pub struct InterfaceAddressIterator {
+    base: *mut libc::ifaddrs,
+    next: *mut libc::ifaddrs,
+}
+
+impl Iterator for InterfaceAddressIterator {
+    type Item = InterfaceAddress;
+
+    fn next(&mut self) -> Option<InterfaceAddress> {
+        if self.next.is_null() {
+            return None;
+        }
+        unsafe {
+            let ifa = &*self.next;
+            self.next = ifa.ifa_next;
+            Some(InterfaceAddress::from_ifaddrs(ifa))
+        }
+    }
+}
+
+impl Drop for InterfaceAddressIterator {
+    fn drop(&mut self) {
+        unsafe { libc::freeifaddrs(self.base) };
+    }
+}
+
+/// Get the addresses of all network interfaces.
+///
+/// # Example
+///
+/// ```
+/// use nix::net::if_::getifaddrs;
+///
+/// for ifaddr in getifaddrs().unwrap() {
+///     if let Some(address) = ifaddr.address {
+///         println!("{}: {}", ifaddr.interface_name, address);
+///     }
+/// }
+/// ```
+///
+/// [Further reading](http://man7.org/linux/man-pages/man3/getifaddrs.3.html)
+pub fn getifaddrs() -> Result<InterfaceAddressIterator> {
+    let mut addrs: *mut libc::ifaddrs = ptr::null_mut();
+    let res = unsafe { libc::getifaddrs(&mut addrs) };
+    try!(Errno::result(res));
+    Ok(InterfaceAddressIterator {
+        base: addrs,
+        next: addrs,
+    })
+}
+
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::linux::*;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod linux {
+    use libc::{c_char, c_int, c_short, c_uchar, c_uint, c_ulong, c_ushort, sa_family_t};
+    use std::{mem, ptr};
+    use std::ffi::CStr;
+    use std::os::unix::io::RawFd;
+    use {Errno, Error, Result, NixPath};
+    use sys::socket::{AF_INET, ARPHRD_ETHER, Ipv4Addr, sockaddr, sockaddr_in};
+    use super::{IF_NAMESIZE, InterfaceFlags, IFF_UP, IFF_PROMISC};
+
+    mod ioctl {
+        const SIOCGIFFLAGS: u32 = 0x8913;
+        const SIOCSIFFLAGS: u32 = 0x8914;
+        const SIOCGIFADDR: u32 = 0x8915;
+        const SIOCSIFADDR: u32 = 0x8916;
+        const SIOCGIFNETMASK: u32 = 0x891b;
+        const SIOCSIFNETMASK: u32 = 0x891c;
+        const SIOCGIFMTU: u32 = 0x8921;
+        const SIOCSIFMTU: u32 = 0x8922;
+        const SIOCSIFNAME: u32 = 0x8923;
+        const SIOCSIFHWADDR: u32 = 0x8924;
+        const SIOCGIFHWADDR: u32 = 0x8927;
+
+        ioctl!(siocgifflags with SIOCGIFFLAGS);
+        ioctl!(siocsifflags with SIOCSIFFLAGS);
+        ioctl!(siocgifaddr with SIOCGIFADDR);
+        ioctl!(siocsifaddr with SIOCSIFADDR);
+        ioctl!(siocgifnetmask with SIOCGIFNETMASK);
+        ioctl!(siocsifnetmask with SIOCSIFNETMASK);
+        ioctl!(siocgifmtu with SIOCGIFMTU);
+        ioctl!(siocsifmtu with SIOCSIFMTU);
+        ioctl!(siocsifname with SIOCSIFNAME);
+        ioctl!(siocsifhwaddr with SIOCSIFHWADDR);
+        ioctl!(siocgifhwaddr with SIOCGIFHWADDR);
+    }
+
+    // The largest member of the ifreq union, which also gives it its
+    // alignment.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct ifmap {
+        mem_start: c_ulong,
+        mem_end: c_ulong,
+        base_addr: c_ushort,
+        irq: c_uchar,
+        dma: c_uchar,
+        port: c_uchar,
+    }
+
+    /// The request structure of the interface ioctls, holding an interface
+    /// name and one of a flags word, an integer, an address or a new name.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man7/netdevice.7.html)
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct ifreq {
+        ifr_name: [c_char; IF_NAMESIZE],
+        ifr_ifru: ifmap,
+    }
+
+    impl ifreq {
+        /// Create a request for the interface `name`, failing with
+        /// `ENAMETOOLONG` if it doesn't fit into `IF_NAMESIZE`.
+        pub fn new<P: ?Sized + NixPath>(name: &P) -> Result<ifreq> {
+            let mut req: ifreq = unsafe { mem::zeroed() };
+            try!(try!(name.with_nix_path(|cstr| copy_name(&mut req.ifr_name, cstr))));
+            Ok(req)
+        }
+
+        /// The interface name.
+        pub fn name(&self) -> &CStr {
+            unsafe { CStr::from_ptr(self.ifr_name.as_ptr()) }
+        }
+
+        /// The `ifr_flags` member.
+        pub fn flags(&self) -> c_short {
+            unsafe { *(&self.ifr_ifru as *const ifmap as *const c_short) }
+        }
+
+        pub fn set_flags(&mut self, flags: c_short) {
+            unsafe { *(&mut self.ifr_ifru as *mut ifmap as *mut c_short) = flags };
+        }
+
+        /// The `ifr_mtu`, `ifr_ifindex` or `ifr_metric` member.
+        pub fn int(&self) -> c_int {
+            unsafe { *(&self.ifr_ifru as *const ifmap as *const c_int) }
+        }
+
+        pub fn set_int(&mut self, val: c_int) {
+            unsafe { *(&mut self.ifr_ifru as *mut ifmap as *mut c_int) = val };
+        }
+
+        /// The `ifr_addr`, `ifr_netmask` or `ifr_hwaddr` member.
+        pub fn addr(&self) -> &sockaddr {
+            unsafe { &*(&self.ifr_ifru as *const ifmap as *const sockaddr) }
+        }
+
+        pub fn set_addr(&mut self, addr: &sockaddr) {
+            unsafe { *(&mut self.ifr_ifru as *mut ifmap as *mut sockaddr) = *addr };
+        }
+
+        /// Set the `ifr_newname` member.
+        pub fn set_newname<P: ?Sized + NixPath>(&mut self, name: &P) -> Result<()> {
+            let newname = unsafe { &mut *(&mut self.ifr_ifru as *mut ifmap as *mut [c_char; IF_NAMESIZE]) };
+            try!(name.with_nix_path(|cstr| copy_name(newname, cstr)))
+        }
+
+        fn as_mut_ptr(&mut self) -> *mut u8 {
+            self as *mut ifreq as *mut u8
+        }
+    }
+
+    fn copy_name(dst: &mut [c_char; IF_NAMESIZE], name: &CStr) -> Result<()> {
+        let bytes = name.to_bytes_with_nul();
+        if bytes.len() > IF_NAMESIZE {
+            return Err(Error::Sys(Errno::ENAMETOOLONG));
+        }
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, dst.as_mut_ptr(), bytes.len());
+        }
+        Ok(())
+    }
+
+    fn ipv4_to_sockaddr(addr: &Ipv4Addr) -> sockaddr {
+        let mut sin: sockaddr_in = unsafe { mem::zeroed() };
+        sin.sin_family = AF_INET as sa_family_t;
+        sin.sin_addr = addr.0;
+        unsafe { mem::transmute(sin) }
+    }
+
+    fn sockaddr_to_ipv4(sa: &sockaddr) -> Result<Ipv4Addr> {
+        if sa.sa_family as c_int != AF_INET {
+            return Err(Error::Sys(Errno::EAFNOSUPPORT));
+        }
+        let sin = unsafe { &*(sa as *const sockaddr as *const sockaddr_in) };
+        Ok(Ipv4Addr(sin.sin_addr))
+    }
+
+    /// Get the flags of the interface `name`, using the control socket
+    /// `sock`, which can be any `AF_INET` socket.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man7/netdevice.7.html)
+    pub fn if_flags<P: ?Sized + NixPath>(sock: RawFd, name: &P) -> Result<InterfaceFlags> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifflags(sock, req.as_mut_ptr()) });
+        Ok(InterfaceFlags::from_bits_truncate(req.flags() as u16 as c_uint))
+    }
+
+    /// Set the flags of the interface `name`.  Only some of the flags, like
+    /// `IFF_UP` and `IFF_PROMISC`, can be changed.
+    pub fn if_set_flags<P: ?Sized + NixPath>(sock: RawFd, name: &P, flags: InterfaceFlags) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        req.set_flags(flags.bits() as c_short);
+        unsafe { ioctl::siocsifflags(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    fn if_update_flags<P: ?Sized + NixPath>(sock: RawFd, name: &P, flag: InterfaceFlags, on: bool) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifflags(sock, req.as_mut_ptr()) });
+        let mut flags = InterfaceFlags::from_bits_truncate(req.flags() as u16 as c_uint);
+        if on {
+            flags.insert(flag);
+        } else {
+            flags.remove(flag);
+        }
+        req.set_flags(flags.bits() as c_short);
+        unsafe { ioctl::siocsifflags(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    /// Bring the interface `name` up or down, leaving its other flags alone.
+    pub fn if_set_up<P: ?Sized + NixPath>(sock: RawFd, name: &P, up: bool) -> Result<()> {
+        if_update_flags(sock, name, IFF_UP, up)
+    }
+
+    /// Turn promiscuous mode of the interface `name` on or off, leaving its
+    /// other flags alone.
+    pub fn if_set_promisc<P: ?Sized + NixPath>(sock: RawFd, name: &P, promisc: bool) -> Result<()> {
+        if_update_flags(sock, name, IFF_PROMISC, promisc)
+    }
+
+    /// Get the MTU of the interface `name`.
+    pub fn if_mtu<P: ?Sized + NixPath>(sock: RawFd, name: &P) -> Result<c_int> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifmtu(sock, req.as_mut_ptr()) });
+        Ok(req.int())
+    }
+
+    /// Set the MTU of the interface `name`.
+    pub fn if_set_mtu<P: ?Sized + NixPath>(sock: RawFd, name: &P, mtu: c_int) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        req.set_int(mtu);
+        unsafe { ioctl::siocsifmtu(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    /// Get the Ethernet hardware address of the interface `name`.  Addresses
+    /// of other hardware types are truncated to six bytes.
+    pub fn if_hwaddr<P: ?Sized + NixPath>(sock: RawFd, name: &P) -> Result<[u8; 6]> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifhwaddr(sock, req.as_mut_ptr()) });
+        let mut hwaddr = [0u8; 6];
+        for (dst, src) in hwaddr.iter_mut().zip(req.addr().sa_data.iter()) {
+            *dst = *src as u8;
+        }
+        Ok(hwaddr)
+    }
+
+    /// Set the Ethernet hardware address of the interface `name`.  The
+    /// interface usually has to be down.
+    pub fn if_set_hwaddr<P: ?Sized + NixPath>(sock: RawFd, name: &P, hwaddr: &[u8; 6]) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        let mut sa: sockaddr = unsafe { mem::zeroed() };
+        sa.sa_family = ARPHRD_ETHER as sa_family_t;
+        for (dst, src) in sa.sa_data.iter_mut().zip(hwaddr.iter()) {
+            *dst = *src as c_char;
+        }
+        req.set_addr(&sa);
+        unsafe { ioctl::siocsifhwaddr(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    /// Get the IPv4 address of the interface `name`.
+    pub fn if_addr<P: ?Sized + NixPath>(sock: RawFd, name: &P) -> Result<Ipv4Addr> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifaddr(sock, req.as_mut_ptr()) });
+        sockaddr_to_ipv4(req.addr())
+    }
+
+    /// Set the IPv4 address of the interface `name`.
+    pub fn if_set_addr<P: ?Sized + NixPath>(sock: RawFd, name: &P, addr: &Ipv4Addr) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        req.set_addr(&ipv4_to_sockaddr(addr));
+        unsafe { ioctl::siocsifaddr(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    /// Get the IPv4 netmask of the interface `name`.
+    pub fn if_netmask<P: ?Sized + NixPath>(sock: RawFd, name: &P) -> Result<Ipv4Addr> {
+        let mut req = try!(ifreq::new(name));
+        try!(unsafe { ioctl::siocgifnetmask(sock, req.as_mut_ptr()) });
+        sockaddr_to_ipv4(req.addr())
+    }
+
+    /// Set the IPv4 netmask of the interface `name`.  The interface needs to
+    /// have an IPv4 address already.
+    pub fn if_set_netmask<P: ?Sized + NixPath>(sock: RawFd, name: &P, netmask: &Ipv4Addr) -> Result<()> {
+        let mut req = try!(ifreq::new(name));
+        req.set_addr(&ipv4_to_sockaddr(netmask));
+        unsafe { ioctl::siocsifnetmask(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+    /// Rename the interface `name` to `newname`.  The interface has to be
+    /// down.
+    pub fn if_set_name<P1, P2>(sock: RawFd, name: &P1, newname: &P2) -> Result<()>
+        where P1: ?Sized + NixPath, P2: ?Sized + NixPath
+    {
+        let mut req = try!(ifreq::new(name));
+        try!(req.set_newname(newname));
+        unsafe { ioctl::siocsifname(sock, req.as_mut_ptr()) }.map(drop)
+    }
+
+}
@@ -1,3 +1,6 @@
 // To avoid clashing with the keyword "if", we use "if_" as the module name.
 // The original header is called "net/if.h".
 pub mod if_;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod tun;
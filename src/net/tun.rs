@@ -0,0 +1,154 @@
+//! TUN/TAP virtual network devices.
+//!
+//! A TUN device exchanges IP packets and a TAP device Ethernet frames
+//! between its interface and the file descriptor it was created on.
+//!
+//! [Further reading](https://www.kernel.org/doc/Documentation/networking/tuntap.txt)
+
+use libc::{c_int, c_short, gid_t, uid_t};
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+use {Result, NixPath};
+use fcntl::{self, O_CLOEXEC, O_RDWR};
+use net::if_::ifreq;
+use sys::stat::Mode;
+use unistd;
+
+/// The control device TUN/TAP devices are created on.
+pub const TUN_DEVICE: &'static str = "/dev/net/tun";
+
+mod ioctl {
+    use libc::c_int;
+
+    const TUN_IOC_MAGIC: u8 = b'T';
+
+    // TUNSETPERSIST, TUNSETOWNER and TUNSETGROUP take their argument by
+    // value rather than through the pointer.
+    ioctl!(write tunsetiff with TUN_IOC_MAGIC, 202; c_int);
+    ioctl!(write tunsetpersist with TUN_IOC_MAGIC, 203; c_int);
+    ioctl!(write tunsetowner with TUN_IOC_MAGIC, 204; c_int);
+    ioctl!(write tunsetgroup with TUN_IOC_MAGIC, 206; c_int);
+    ioctl!(write tunsetqueue with TUN_IOC_MAGIC, 217; c_int);
+}
+
+bitflags!(
+    /// Flags for creating a TUN/TAP device with `Tun::new`
+    pub flags TunFlags: c_short {
+        /// A TUN device, exchanging IP packets.
+        const IFF_TUN         = 0x0001,
+        /// A TAP device, exchanging Ethernet frames.
+        const IFF_TAP         = 0x0002,
+        /// Don't prepend the four byte packet information header.
+        const IFF_NO_PI       = 0x1000,
+        /// Prepend a `virtio_net_hdr` to each packet.
+        const IFF_VNET_HDR    = 0x4000,
+        /// Fail with `EBUSY` if the device exists already.
+        const IFF_TUN_EXCL    = 0x8000u16 as c_short,
+        /// Create a device with multiple queues, each attached to its own
+        /// file descriptor.
+        const IFF_MULTI_QUEUE = 0x0100,
+    }
+);
+
+// Flags for TUNSETQUEUE
+const IFF_ATTACH_QUEUE: c_short = 0x0200;
+const IFF_DETACH_QUEUE: c_short = 0x0400;
+
+/// An open queue of a TUN/TAP device.  Reading it receives the packets sent
+/// to the interface, writing it sends packets from the interface.  The file
+/// descriptor is closed when it is dropped, which also deletes the device
+/// unless it was made persistent.
+#[derive(Debug)]
+pub struct Tun {
+    fd: RawFd,
+    name: String,
+}
+
+impl Tun {
+    /// Create the TUN/TAP device `name`, or attach to it if it exists.  An
+    /// empty name or one containing `%d`, like `"tap%d"`, lets the kernel
+    /// pick the name, which is available from `name()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use nix::net::tun::{Tun, IFF_TUN, IFF_NO_PI};
+    ///
+    /// let tun = Tun::new("tun%d", IFF_TUN | IFF_NO_PI).unwrap();
+    /// println!("created {}", tun.name());
+    /// ```
+    pub fn new<P: ?Sized + NixPath>(name: &P, flags: TunFlags) -> Result<Tun> {
+        let mut req = try!(ifreq::new(name));
+        req.set_flags(flags.bits());
+        let fd = try!(fcntl::open(TUN_DEVICE, O_RDWR | O_CLOEXEC, Mode::empty()));
+        let res = unsafe { ioctl::tunsetiff(fd, &mut req as *mut ifreq as *const c_int) };
+        if let Err(e) = res {
+            let _ = unistd::close(fd);
+            return Err(e);
+        }
+        Ok(Tun {
+            fd: fd,
+            name: req.name().to_string_lossy().into_owned(),
+        })
+    }
+
+    /// The name of the interface.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Keep the device once its last file descriptor is closed, or delete it
+    /// again then.
+    pub fn set_persist(&self, persist: bool) -> Result<()> {
+        unsafe { ioctl::tunsetpersist(self.fd, persist as usize as *const c_int) }.map(drop)
+    }
+
+    /// Allow the user `uid` to attach to a persistent device.
+    pub fn set_owner(&self, uid: uid_t) -> Result<()> {
+        unsafe { ioctl::tunsetowner(self.fd, uid as usize as *const c_int) }.map(drop)
+    }
+
+    /// Allow the members of the group `gid` to attach to a persistent device.
+    pub fn set_group(&self, gid: gid_t) -> Result<()> {
+        unsafe { ioctl::tunsetgroup(self.fd, gid as usize as *const c_int) }.map(drop)
+    }
+
+    fn set_queue(&self, flags: c_short) -> Result<()> {
+        let mut req = try!(ifreq::new(""));
+        req.set_flags(flags);
+        unsafe { ioctl::tunsetqueue(self.fd, &mut req as *mut ifreq as *const c_int) }.map(drop)
+    }
+
+    /// Re-enable a queue of an `IFF_MULTI_QUEUE` device that was disabled
+    /// with `detach_queue`.
+    pub fn attach_queue(&self) -> Result<()> {
+        self.set_queue(IFF_ATTACH_QUEUE)
+    }
+
+    /// Stop the kernel from delivering packets to this queue of an
+    /// `IFF_MULTI_QUEUE` device, without closing it.
+    pub fn detach_queue(&self) -> Result<()> {
+        self.set_queue(IFF_DETACH_QUEUE)
+    }
+}
+
+impl Drop for Tun {
+    fn drop(&mut self) {
+        if self.fd >= 0 {
+            let _ = unistd::close(self.fd);
+        }
+    }
+}
+
+impl AsRawFd for Tun {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for Tun {
+    fn into_raw_fd(mut self) -> RawFd {
+        let fd = self.fd;
+        self.fd = -1;
+        fd
+    }
+}
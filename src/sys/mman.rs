@@ -188,6 +188,7 @@ mod ffi {
         pub fn munlock(addr: *const c_void, len: size_t) -> c_int;
         pub fn madvise (addr: *const c_void, len: size_t, advice: c_int) -> c_int;
         pub fn msync (addr: *const c_void, len: size_t, flags: c_int) -> c_int;
+        pub fn mprotect (addr: *mut c_void, len: size_t, prot: c_int) -> c_int;
     }
 }
 
@@ -215,6 +216,12 @@ pub unsafe fn munmap(addr: *mut c_void, len: size_t) -> Result<()> {
     Errno::result(ffi::munmap(addr, len)).map(drop)
 }
 
+/// Changes the access protections of the pages that contain any part of the
+/// address range starting at `addr` (which must be page aligned).
+pub unsafe fn mprotect(addr: *mut c_void, length: size_t, prot: ProtFlags) -> Result<()> {
+    Errno::result(ffi::mprotect(addr, length, prot.bits())).map(drop)
+}
+
 pub unsafe fn madvise(addr: *const c_void, length: size_t, advise: MmapAdvise) -> Result<()> {
     Errno::result(ffi::madvise(addr, length, advise)).map(drop)
 }
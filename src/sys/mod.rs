@@ -52,10 +52,16 @@ pub mod uio;
 
 pub mod time;
 
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos",
+          target_os = "ios", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd"))]
+pub mod xattr;
+
 #[cfg(all(target_os = "linux",
           any(target_arch = "x86",
               target_arch = "x86_64",
-              target_arch = "arm")),
+              target_arch = "arm",
+              target_arch = "aarch64")),
           )]
 pub mod ptrace;
 
@@ -1,10 +1,18 @@
+use std::{mem, ptr};
 use {Errno, Error, Result};
-use libc::{pid_t, c_void, c_long};
+use libc::{self, pid_t, c_void, c_long, c_int};
+use sys::signal::Signal;
+
+/// The type of the remote address and user-area offset arguments of the typed
+/// ptrace requests.  These are never dereferenced in the tracer, so they may be
+/// passed around safely.
+pub type AddressType = *mut c_void;
 
 #[cfg(all(target_os = "linux",
           any(target_arch = "x86",
               target_arch = "x86_64",
-              target_arch = "arm")),
+              target_arch = "arm",
+              target_arch = "aarch64")),
           )]
 pub mod ptrace {
     use libc::c_int;
@@ -21,13 +29,19 @@ pub mod ptrace {
     pub const PTRACE_CONT:        PtraceRequest = 7;
     pub const PTRACE_KILL:        PtraceRequest = 8;
     pub const PTRACE_SINGLESTEP:  PtraceRequest = 9;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_GETREGS:     PtraceRequest = 12;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_SETREGS:     PtraceRequest = 13;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_GETFPREGS:   PtraceRequest = 14;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_SETFPREGS:   PtraceRequest = 15;
     pub const PTRACE_ATTACH:      PtraceRequest = 16;
     pub const PTRACE_DETACH:      PtraceRequest = 17;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_GETFPXREGS:  PtraceRequest = 18;
+    #[cfg(not(target_arch = "aarch64"))]
     pub const PTRACE_SETFPXREGS:  PtraceRequest = 19;
     pub const PTRACE_SYSCALL:     PtraceRequest = 24;
     pub const PTRACE_SETOPTIONS:  PtraceRequest = 0x4200;
@@ -41,26 +55,159 @@ pub mod ptrace {
     pub const PTRACE_LISTEN:      PtraceRequest = 0x4208;
     pub const PTRACE_PEEKSIGINFO: PtraceRequest = 0x4209;
 
-    pub type PtraceEvent = c_int;
+    pub const PTRACE_EVENT_FORK:       c_int = 1;
+    pub const PTRACE_EVENT_VFORK:      c_int = 2;
+    pub const PTRACE_EVENT_CLONE:      c_int = 3;
+    pub const PTRACE_EVENT_EXEC:       c_int = 4;
+    pub const PTRACE_EVENT_VFORK_DONE: c_int = 5;
+    pub const PTRACE_EVENT_EXIT:       c_int = 6;
+    pub const PTRACE_EVENT_SECCOMP:    c_int = 7;
+    pub const PTRACE_EVENT_STOP:       c_int = 128;
+
+    bitflags!(
+        pub flags PtraceOptions: c_int {
+            const PTRACE_O_TRACESYSGOOD   = 1,
+            const PTRACE_O_TRACEFORK      = (1 << PTRACE_EVENT_FORK),
+            const PTRACE_O_TRACEVFORK     = (1 << PTRACE_EVENT_VFORK),
+            const PTRACE_O_TRACECLONE     = (1 << PTRACE_EVENT_CLONE),
+            const PTRACE_O_TRACEEXEC      = (1 << PTRACE_EVENT_EXEC),
+            const PTRACE_O_TRACEVFORKDONE = (1 << PTRACE_EVENT_VFORK_DONE),
+            const PTRACE_O_TRACEEXIT      = (1 << PTRACE_EVENT_EXIT),
+            const PTRACE_O_TRACESECCOMP   = (1 << PTRACE_EVENT_SECCOMP),
+            const PTRACE_O_EXITKILL       = (1 << 20),
+        }
+    );
+
+    pub type NoteType = c_int;
+
+    pub const NT_PRSTATUS: NoteType = 1;
+    pub const NT_PRFPREG:  NoteType = 2;
+}
+
+/// The kind of event reported by a `WaitStatus::PtraceEvent` stop.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(i32)]
+pub enum PtraceEvent {
+    /// The tracee called `fork`; the new pid is available from `getevent`.
+    Fork = ptrace::PTRACE_EVENT_FORK,
+    /// The tracee called `vfork`; the new pid is available from `getevent`.
+    VFork = ptrace::PTRACE_EVENT_VFORK,
+    /// The tracee called `clone`; the new tid is available from `getevent`.
+    Clone = ptrace::PTRACE_EVENT_CLONE,
+    /// The tracee called `execve`; its former thread id is available from
+    /// `getevent`.
+    Exec = ptrace::PTRACE_EVENT_EXEC,
+    /// The child of a `vfork` released the tracee.
+    VForkDone = ptrace::PTRACE_EVENT_VFORK_DONE,
+    /// The tracee is about to exit; its wait status is available from
+    /// `getevent`.
+    Exit = ptrace::PTRACE_EVENT_EXIT,
+    /// A seccomp `SECCOMP_RET_TRACE` rule matched; its data is available from
+    /// `getevent`.
+    Seccomp = ptrace::PTRACE_EVENT_SECCOMP,
+    /// A tracee attached with `seize` entered a group-stop or was stopped by
+    /// `interrupt`.
+    Stop = ptrace::PTRACE_EVENT_STOP,
+}
+
+impl PtraceEvent {
+    /// Converts the event number carried by `WaitStatus::PtraceEvent`.
+    pub fn from_c_int(event: c_int) -> Result<PtraceEvent> {
+        use self::PtraceEvent::*;
+
+        match event {
+            ptrace::PTRACE_EVENT_FORK => Ok(Fork),
+            ptrace::PTRACE_EVENT_VFORK => Ok(VFork),
+            ptrace::PTRACE_EVENT_CLONE => Ok(Clone),
+            ptrace::PTRACE_EVENT_EXEC => Ok(Exec),
+            ptrace::PTRACE_EVENT_VFORK_DONE => Ok(VForkDone),
+            ptrace::PTRACE_EVENT_EXIT => Ok(Exit),
+            ptrace::PTRACE_EVENT_SECCOMP => Ok(Seccomp),
+            ptrace::PTRACE_EVENT_STOP => Ok(Stop),
+            _ => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub use libc::{user_regs_struct, user_fpregs_struct};
+
+/// The general purpose registers, as read by `PTRACE_GETREGS` (`struct
+/// user_regs` in the kernel's `asm/ptrace.h`).
+#[cfg(target_arch = "arm")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct user_regs_struct {
+    pub uregs: [libc::c_ulong; 18],
+}
+
+/// The floating point registers, as read by `PTRACE_GETFPREGS` (`struct
+/// user_fp` in the kernel's `asm/user.h`).
+#[cfg(target_arch = "arm")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct user_fpregs_struct {
+    pub fpregs: [[u32; 3]; 8],
+    pub fpsr: u32,
+    pub fpcr: u32,
+    pub ftype: [u8; 8],
+    pub init_flag: u32,
+}
+
+/// The general purpose registers, as read by `PTRACE_GETREGSET` with
+/// `NT_PRSTATUS` (`struct user_pt_regs` in the kernel's `asm/ptrace.h`).
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct user_regs_struct {
+    pub regs: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+/// The FP/SIMD registers, as read by `PTRACE_GETREGSET` with `NT_PRFPREG`
+/// (`struct user_fpsimd_state` in the kernel's `asm/ptrace.h`).  Each vector
+/// register is stored as two 64 bit halves, least significant first.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct user_fpregs_struct {
+    pub vregs: [[u64; 2]; 32],
+    pub fpsr: u32,
+    pub fpcr: u32,
+    pub __reserved: [u32; 2],
+}
+
+/// A register set which can be transferred with `getregset` and `setregset`.
+pub trait RegisterSet {
+    /// The in-memory layout of the register set.
+    type Regs;
+
+    /// The `NT_*` note type that identifies the register set to the kernel.
+    fn note_type() -> ptrace::NoteType;
+}
+
+/// The general purpose registers, as `user_regs_struct`.
+pub enum NtPrstatus {}
+
+impl RegisterSet for NtPrstatus {
+    type Regs = user_regs_struct;
+
+    fn note_type() -> ptrace::NoteType {
+        ptrace::NT_PRSTATUS
+    }
+}
+
+/// The floating point registers, as `user_fpregs_struct`.
+pub enum NtPrfpreg {}
 
-    pub const PTRACE_EVENT_FORK:       PtraceEvent = 1;
-    pub const PTRACE_EVENT_VFORK:      PtraceEvent = 2;
-    pub const PTRACE_EVENT_CLONE:      PtraceEvent = 3;
-    pub const PTRACE_EVENT_EXEC:       PtraceEvent = 4;
-    pub const PTRACE_EVENT_VFORK_DONE: PtraceEvent = 5;
-    pub const PTRACE_EVENT_EXIT:       PtraceEvent = 6;
-    pub const PTRACE_EVENT_SECCOMP:    PtraceEvent = 6;
-    pub const PTRACE_EVENT_STOP:       PtraceEvent = 128;
+impl RegisterSet for NtPrfpreg {
+    type Regs = user_fpregs_struct;
 
-    pub type PtraceOptions = c_int;
-    pub const PTRACE_O_TRACESYSGOOD: PtraceOptions   = 1;
-    pub const PTRACE_O_TRACEFORK: PtraceOptions      = (1 << PTRACE_EVENT_FORK);
-    pub const PTRACE_O_TRACEVFORK: PtraceOptions     = (1 << PTRACE_EVENT_VFORK);
-    pub const PTRACE_O_TRACECLONE: PtraceOptions     = (1 << PTRACE_EVENT_CLONE);
-    pub const PTRACE_O_TRACEEXEC: PtraceOptions      = (1 << PTRACE_EVENT_EXEC);
-    pub const PTRACE_O_TRACEVFORKDONE: PtraceOptions = (1 << PTRACE_EVENT_VFORK_DONE);
-    pub const PTRACE_O_TRACEEXIT: PtraceOptions      = (1 << PTRACE_EVENT_EXIT);
-    pub const PTRACE_O_TRACESECCOMP: PtraceOptions   = (1 << PTRACE_EVENT_SECCOMP);
+    fn note_type() -> ptrace::NoteType {
+        ptrace::NT_PRFPREG
+    }
 }
 
 mod ffi {
@@ -98,7 +245,222 @@ fn ptrace_other(request: ptrace::PtraceRequest, pid: pid_t, addr: *mut c_void, d
 /// Set options, as with `ptrace(PTRACE_SETOPTIONS,...)`.
 pub fn ptrace_setoptions(pid: pid_t, options: ptrace::PtraceOptions) -> Result<()> {
     use self::ptrace::*;
-    use std::ptr;
 
-    ptrace(PTRACE_SETOPTIONS, pid, ptr::null_mut(), options as *mut c_void).map(drop)
+    ptrace(PTRACE_SETOPTIONS, pid, ptr::null_mut(), options.bits() as *mut c_void).map(drop)
+}
+
+/// Converts an optional signal into the `data` argument expected by the
+/// restarting requests, where 0 means that no signal is delivered.
+fn signal_data<T: Into<Option<Signal>>>(sig: T) -> *mut c_void {
+    match sig.into() {
+        Some(s) => c_int::from(s) as *mut c_void,
+        None => ptr::null_mut(),
+    }
+}
+
+/// Makes the calling process a tracee of its parent, as with
+/// `ptrace(PTRACE_TRACEME, ...)`.
+///
+/// This is usually called in a child between `fork` and `execve`.
+pub fn traceme() -> Result<()> {
+    ptrace_other(ptrace::PTRACE_TRACEME, 0, ptr::null_mut(), ptr::null_mut()).map(drop)
+}
+
+/// Attaches to the process `pid` and stops it with `SIGSTOP`, as with
+/// `ptrace(PTRACE_ATTACH, ...)`.
+pub fn attach(pid: pid_t) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_ATTACH, pid, ptr::null_mut(), ptr::null_mut()).map(drop)
+}
+
+/// Attaches to the process `pid` without stopping it, as with
+/// `ptrace(PTRACE_SEIZE, ...)`.  `options` are applied as by
+/// `ptrace_setoptions`.
+pub fn seize(pid: pid_t, options: ptrace::PtraceOptions) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SEIZE, pid, ptr::null_mut(), options.bits() as *mut c_void).map(drop)
+}
+
+/// Detaches from the tracee `pid` and restarts it, delivering `sig` if
+/// given, as with `ptrace(PTRACE_DETACH, ...)`.
+pub fn detach<T: Into<Option<Signal>>>(pid: pid_t, sig: T) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_DETACH, pid, ptr::null_mut(), signal_data(sig)).map(drop)
+}
+
+/// Restarts the stopped tracee `pid`, delivering `sig` if given, as with
+/// `ptrace(PTRACE_CONT, ...)`.
+pub fn cont<T: Into<Option<Signal>>>(pid: pid_t, sig: T) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_CONT, pid, ptr::null_mut(), signal_data(sig)).map(drop)
+}
+
+/// Restarts the stopped tracee `pid` until the next entry to or exit from a
+/// system call, delivering `sig` if given, as with `ptrace(PTRACE_SYSCALL, ...)`.
+pub fn syscall<T: Into<Option<Signal>>>(pid: pid_t, sig: T) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SYSCALL, pid, ptr::null_mut(), signal_data(sig)).map(drop)
+}
+
+/// Restarts the stopped tracee `pid` for a single instruction, delivering
+/// `sig` if given, as with `ptrace(PTRACE_SINGLESTEP, ...)`.
+pub fn step<T: Into<Option<Signal>>>(pid: pid_t, sig: T) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SINGLESTEP, pid, ptr::null_mut(), signal_data(sig)).map(drop)
+}
+
+/// Stops a tracee attached with `seize`, as with `ptrace(PTRACE_INTERRUPT, ...)`.
+pub fn interrupt(pid: pid_t) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_INTERRUPT, pid, ptr::null_mut(), ptr::null_mut()).map(drop)
+}
+
+/// Restarts a tracee attached with `seize` in a group-stop without resuming
+/// execution, as with `ptrace(PTRACE_LISTEN, ...)`.
+pub fn listen(pid: pid_t) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_LISTEN, pid, ptr::null_mut(), ptr::null_mut()).map(drop)
+}
+
+/// Kills the tracee `pid`, as with `ptrace(PTRACE_KILL, ...)`.
+pub fn kill(pid: pid_t) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_KILL, pid, ptr::null_mut(), ptr::null_mut()).map(drop)
+}
+
+/// Reads a word at `addr` in the tracee's memory, as with
+/// `ptrace(PTRACE_PEEKDATA, ...)`.
+pub fn peekdata(pid: pid_t, addr: AddressType) -> Result<c_long> {
+    ptrace_peek(ptrace::PTRACE_PEEKDATA, pid, addr, ptr::null_mut())
+}
+
+/// Writes the word `data` at `addr` in the tracee's memory, as with
+/// `ptrace(PTRACE_POKEDATA, ...)`.
+pub fn pokedata(pid: pid_t, addr: AddressType, data: c_long) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_POKEDATA, pid, addr, data as *mut c_void).map(drop)
+}
+
+/// Reads a word at `offset` in the tracee's user area, as with
+/// `ptrace(PTRACE_PEEKUSER, ...)`.
+pub fn peekuser(pid: pid_t, offset: AddressType) -> Result<c_long> {
+    ptrace_peek(ptrace::PTRACE_PEEKUSER, pid, offset, ptr::null_mut())
+}
+
+/// Writes the word `data` at `offset` in the tracee's user area, as with
+/// `ptrace(PTRACE_POKEUSER, ...)`.
+pub fn pokeuser(pid: pid_t, offset: AddressType, data: c_long) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_POKEUSER, pid, offset, data as *mut c_void).map(drop)
+}
+
+/// Performs a request which fills in a structure of type `T` in the tracer.
+fn ptrace_get_data<T>(request: ptrace::PtraceRequest, pid: pid_t) -> Result<T> {
+    let mut data: T = unsafe { mem::uninitialized() };
+    try!(ptrace_other(request, pid, ptr::null_mut(), &mut data as *mut T as *mut c_void));
+    Ok(data)
+}
+
+/// Gets the message associated with the most recent ptrace event stop of the
+/// tracee, as with `ptrace(PTRACE_GETEVENTMSG, ...)`.  See `PtraceEvent` for
+/// its meaning.
+pub fn getevent(pid: pid_t) -> Result<c_long> {
+    ptrace_get_data(ptrace::PTRACE_GETEVENTMSG, pid)
+}
+
+/// Gets the `siginfo_t` of the signal that caused the tracee to stop, as with
+/// `ptrace(PTRACE_GETSIGINFO, ...)`.
+pub fn getsiginfo(pid: pid_t) -> Result<libc::siginfo_t> {
+    ptrace_get_data(ptrace::PTRACE_GETSIGINFO, pid)
+}
+
+/// Replaces the `siginfo_t` of the signal that caused the tracee to stop, as
+/// with `ptrace(PTRACE_SETSIGINFO, ...)`.
+pub fn setsiginfo(pid: pid_t, siginfo: &libc::siginfo_t) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SETSIGINFO, pid, ptr::null_mut(),
+                 siginfo as *const libc::siginfo_t as *mut c_void).map(drop)
+}
+
+/// Gets the register set `S` of the tracee, as with
+/// `ptrace(PTRACE_GETREGSET, ...)`.  Fails with `EIO` if the kernel filled in
+/// less than a whole `S::Regs`.
+///
+/// # Example
+///
+/// ```no_run
+/// use nix::sys::ptrace::{getregset, NtPrstatus};
+/// # let pid = 0;
+/// let regs = getregset::<NtPrstatus>(pid).unwrap();
+/// ```
+pub fn getregset<S: RegisterSet>(pid: pid_t) -> Result<S::Regs> {
+    let mut regs: S::Regs = unsafe { mem::uninitialized() };
+    let mut iov = libc::iovec {
+        iov_base: &mut regs as *mut S::Regs as *mut c_void,
+        iov_len: mem::size_of::<S::Regs>(),
+    };
+    try!(ptrace_other(ptrace::PTRACE_GETREGSET, pid, S::note_type() as *mut c_void,
+                      &mut iov as *mut libc::iovec as *mut c_void));
+    // The kernel shrinks iov_len to the size of the register set it wrote
+    if iov.iov_len != mem::size_of::<S::Regs>() {
+        return Err(Error::Sys(Errno::EIO));
+    }
+    Ok(regs)
+}
+
+/// Sets the register set `S` of the tracee, as with
+/// `ptrace(PTRACE_SETREGSET, ...)`.
+pub fn setregset<S: RegisterSet>(pid: pid_t, mut regs: S::Regs) -> Result<()> {
+    let mut iov = libc::iovec {
+        iov_base: &mut regs as *mut S::Regs as *mut c_void,
+        iov_len: mem::size_of::<S::Regs>(),
+    };
+    ptrace_other(ptrace::PTRACE_SETREGSET, pid, S::note_type() as *mut c_void,
+                 &mut iov as *mut libc::iovec as *mut c_void).map(drop)
+}
+
+/// Gets the general purpose registers of the tracee, as with
+/// `ptrace(PTRACE_GETREGS, ...)`.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn getregs(pid: pid_t) -> Result<user_regs_struct> {
+    ptrace_get_data(ptrace::PTRACE_GETREGS, pid)
+}
+
+/// Gets the general purpose registers of the tracee.  AArch64 has no
+/// `PTRACE_GETREGS`, so this uses `getregset::<NtPrstatus>`.
+#[cfg(target_arch = "aarch64")]
+pub fn getregs(pid: pid_t) -> Result<user_regs_struct> {
+    getregset::<NtPrstatus>(pid)
+}
+
+/// Sets the general purpose registers of the tracee, as with
+/// `ptrace(PTRACE_SETREGS, ...)`.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn setregs(pid: pid_t, mut regs: user_regs_struct) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SETREGS, pid, ptr::null_mut(),
+                 &mut regs as *mut user_regs_struct as *mut c_void).map(drop)
+}
+
+/// Sets the general purpose registers of the tracee.  AArch64 has no
+/// `PTRACE_SETREGS`, so this uses `setregset::<NtPrstatus>`.
+#[cfg(target_arch = "aarch64")]
+pub fn setregs(pid: pid_t, regs: user_regs_struct) -> Result<()> {
+    setregset::<NtPrstatus>(pid, regs)
+}
+
+/// Gets the floating point registers of the tracee, as with
+/// `ptrace(PTRACE_GETFPREGS, ...)`.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn getfpregs(pid: pid_t) -> Result<user_fpregs_struct> {
+    ptrace_get_data(ptrace::PTRACE_GETFPREGS, pid)
+}
+
+/// Gets the FP/SIMD registers of the tracee.  AArch64 has no
+/// `PTRACE_GETFPREGS`, so this uses `getregset::<NtPrfpreg>`.
+#[cfg(target_arch = "aarch64")]
+pub fn getfpregs(pid: pid_t) -> Result<user_fpregs_struct> {
+    getregset::<NtPrfpreg>(pid)
+}
+
+/// Sets the floating point registers of the tracee, as with
+/// `ptrace(PTRACE_SETFPREGS, ...)`.
+#[cfg(not(target_arch = "aarch64"))]
+pub fn setfpregs(pid: pid_t, mut regs: user_fpregs_struct) -> Result<()> {
+    ptrace_other(ptrace::PTRACE_SETFPREGS, pid, ptr::null_mut(),
+                 &mut regs as *mut user_fpregs_struct as *mut c_void).map(drop)
+}
+
+/// Sets the FP/SIMD registers of the tracee.  AArch64 has no
+/// `PTRACE_SETFPREGS`, so this uses `setregset::<NtPrfpreg>`.
+#[cfg(target_arch = "aarch64")]
+pub fn setfpregs(pid: pid_t, regs: user_fpregs_struct) -> Result<()> {
+    setregset::<NtPrfpreg>(pid, regs)
 }
@@ -9,55 +9,151 @@ use std::mem;
 #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
 use std::os::unix::io::RawFd;
 use std::ptr;
+use sys;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use sys::time::TimeSpec;
 
-// Currently there is only one definition of c_int in libc, as well as only one
-// type for signal constants.
-// We would prefer to use the libc::c_int alias in the repr attribute. Unfortunately
-// this is not (yet) possible.
+/// A signal: either one of the standard signals, or on Linux a real-time
+/// signal.
+///
+/// Use `libc::c_int::from` to get the signal number of a `Signal`, and
+/// `Signal::from_c_int` for the reverse.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-#[repr(i32)]
 pub enum Signal {
-    SIGHUP = libc::SIGHUP,
-    SIGINT = libc::SIGINT,
-    SIGQUIT = libc::SIGQUIT,
-    SIGILL = libc::SIGILL,
-    SIGTRAP = libc::SIGTRAP,
-    SIGABRT = libc::SIGABRT,
-    SIGBUS = libc::SIGBUS,
-    SIGFPE = libc::SIGFPE,
-    SIGKILL = libc::SIGKILL,
-    SIGUSR1 = libc::SIGUSR1,
-    SIGSEGV = libc::SIGSEGV,
-    SIGUSR2 = libc::SIGUSR2,
-    SIGPIPE = libc::SIGPIPE,
-    SIGALRM = libc::SIGALRM,
-    SIGTERM = libc::SIGTERM,
+    SIGHUP,
+    SIGINT,
+    SIGQUIT,
+    SIGILL,
+    SIGTRAP,
+    SIGABRT,
+    SIGBUS,
+    SIGFPE,
+    SIGKILL,
+    SIGUSR1,
+    SIGSEGV,
+    SIGUSR2,
+    SIGPIPE,
+    SIGALRM,
+    SIGTERM,
     #[cfg(all(any(target_os = "linux", target_os = "android", target_os = "emscripten"), not(target_arch = "mips")))]
-    SIGSTKFLT = libc::SIGSTKFLT,
-    SIGCHLD = libc::SIGCHLD,
-    SIGCONT = libc::SIGCONT,
-    SIGSTOP = libc::SIGSTOP,
-    SIGTSTP = libc::SIGTSTP,
-    SIGTTIN = libc::SIGTTIN,
-    SIGTTOU = libc::SIGTTOU,
-    SIGURG = libc::SIGURG,
-    SIGXCPU = libc::SIGXCPU,
-    SIGXFSZ = libc::SIGXFSZ,
-    SIGVTALRM = libc::SIGVTALRM,
-    SIGPROF = libc::SIGPROF,
-    SIGWINCH = libc::SIGWINCH,
-    SIGIO = libc::SIGIO,
+    SIGSTKFLT,
+    SIGCHLD,
+    SIGCONT,
+    SIGSTOP,
+    SIGTSTP,
+    SIGTTIN,
+    SIGTTOU,
+    SIGURG,
+    SIGXCPU,
+    SIGXFSZ,
+    SIGVTALRM,
+    SIGPROF,
+    SIGWINCH,
+    SIGIO,
     #[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
-    SIGPWR = libc::SIGPWR,
-    SIGSYS = libc::SIGSYS,
+    SIGPWR,
+    SIGSYS,
     #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten")))]
-    SIGEMT = libc::SIGEMT,
+    SIGEMT,
     #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten")))]
-    SIGINFO = libc::SIGINFO,
+    SIGINFO,
+    /// A real-time signal, created with `Signal::realtime` or taken from
+    /// `Signal::realtime_iterator`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Realtime(RealtimeOffset),
 }
 
 pub use self::Signal::*;
 
+/// The offset `n` of the real-time signal `SIGRTMIN + n`, which
+/// `Signal::realtime` checked to be in range.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RealtimeOffset(libc::c_int);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RealtimeOffset {
+    /// The offset from `SIGRTMIN`.
+    pub fn get(&self) -> libc::c_int {
+        self.0
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod ffi {
+    use libc::{c_int, pid_t, sigval, sigset_t, siginfo_t, timespec};
+
+    extern {
+        // SIGRTMIN and SIGRTMAX are not constants, because the C library
+        // reserves some real-time signals for its own use.
+        pub fn __libc_current_sigrtmin() -> c_int;
+        pub fn __libc_current_sigrtmax() -> c_int;
+        pub fn sigqueue(pid: pid_t, sig: c_int, value: sigval) -> c_int;
+        pub fn sigwaitinfo(set: *const sigset_t, info: *mut siginfo_t) -> c_int;
+        pub fn sigtimedwait(set: *const sigset_t, info: *mut siginfo_t,
+                            timeout: *const timespec) -> c_int;
+    }
+}
+
+/// The number of the lowest real-time signal available to applications
+/// (`SIGRTMIN`).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn sigrtmin() -> libc::c_int {
+    unsafe { ffi::__libc_current_sigrtmin() }
+}
+
+/// The number of the highest real-time signal (`SIGRTMAX`).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn sigrtmax() -> libc::c_int {
+    unsafe { ffi::__libc_current_sigrtmax() }
+}
+
+impl From<Signal> for libc::c_int {
+    fn from(signal: Signal) -> libc::c_int {
+        match signal {
+            SIGHUP => libc::SIGHUP,
+            SIGINT => libc::SIGINT,
+            SIGQUIT => libc::SIGQUIT,
+            SIGILL => libc::SIGILL,
+            SIGTRAP => libc::SIGTRAP,
+            SIGABRT => libc::SIGABRT,
+            SIGBUS => libc::SIGBUS,
+            SIGFPE => libc::SIGFPE,
+            SIGKILL => libc::SIGKILL,
+            SIGUSR1 => libc::SIGUSR1,
+            SIGSEGV => libc::SIGSEGV,
+            SIGUSR2 => libc::SIGUSR2,
+            SIGPIPE => libc::SIGPIPE,
+            SIGALRM => libc::SIGALRM,
+            SIGTERM => libc::SIGTERM,
+            #[cfg(all(any(target_os = "linux", target_os = "android", target_os = "emscripten"), not(target_arch = "mips")))]
+            SIGSTKFLT => libc::SIGSTKFLT,
+            SIGCHLD => libc::SIGCHLD,
+            SIGCONT => libc::SIGCONT,
+            SIGSTOP => libc::SIGSTOP,
+            SIGTSTP => libc::SIGTSTP,
+            SIGTTIN => libc::SIGTTIN,
+            SIGTTOU => libc::SIGTTOU,
+            SIGURG => libc::SIGURG,
+            SIGXCPU => libc::SIGXCPU,
+            SIGXFSZ => libc::SIGXFSZ,
+            SIGVTALRM => libc::SIGVTALRM,
+            SIGPROF => libc::SIGPROF,
+            SIGWINCH => libc::SIGWINCH,
+            SIGIO => libc::SIGIO,
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
+            SIGPWR => libc::SIGPWR,
+            SIGSYS => libc::SIGSYS,
+            #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten")))]
+            SIGEMT => libc::SIGEMT,
+            #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "emscripten")))]
+            SIGINFO => libc::SIGINFO,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Realtime(n) => sigrtmin() + n.0,
+        }
+    }
+}
+
 #[cfg(all(any(target_os = "linux", target_os = "android", target_os = "emscripten"), not(target_arch = "mips")))]
 const SIGNALS: [Signal; 31] = [
     SIGHUP,
@@ -185,11 +281,58 @@ impl Signal {
     // We do not implement the From trait, because it is supposed to be infallible.
     // With Rust RFC 1542 comes the appropriate trait TryFrom. Once it is
     // implemented, we'll replace this function.
-    #[inline]
     pub fn from_c_int(signum: libc::c_int) -> Result<Signal> {
-        match 0 < signum && signum < NSIG {
-            true => Ok(unsafe { mem::transmute(signum) }),
-            false => Err(Error::invalid_argument()),
+        if let Some(signal) = Signal::iterator().find(|&s| libc::c_int::from(s) == signum) {
+            return Ok(signal);
+        }
+        Signal::from_realtime_c_int(signum)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn from_realtime_c_int(signum: libc::c_int) -> Result<Signal> {
+        Signal::realtime(signum - sigrtmin())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn from_realtime_c_int(_signum: libc::c_int) -> Result<Signal> {
+        Err(Error::invalid_argument())
+    }
+
+    /// Returns the real-time signal `SIGRTMIN + n`, or `EINVAL` if that is
+    /// greater than `SIGRTMAX`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn realtime(n: libc::c_int) -> Result<Signal> {
+        if n >= 0 && n <= sigrtmax() - sigrtmin() {
+            Ok(Realtime(RealtimeOffset(n)))
+        } else {
+            Err(Error::invalid_argument())
+        }
+    }
+
+    /// Iterates over the real-time signals, from `SIGRTMIN` to `SIGRTMAX`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn realtime_iterator() -> RealtimeSignalIterator {
+        RealtimeSignalIterator { next: 0, count: sigrtmax() - sigrtmin() + 1 }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct RealtimeSignalIterator {
+    next: libc::c_int,
+    count: libc::c_int,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Iterator for RealtimeSignalIterator {
+    type Item = Signal;
+
+    fn next(&mut self) -> Option<Signal> {
+        if self.next < self.count {
+            let next_signal = Realtime(RealtimeOffset(self.next));
+            self.next += 1;
+            Some(next_signal)
+        } else {
+            None
         }
     }
 }
@@ -210,6 +353,13 @@ libc_bitflags!{
     }
 }
 
+libc_bitflags!{
+    pub flags SsFlags: libc::c_int {
+        SS_ONSTACK,
+        SS_DISABLE,
+    }
+}
+
 #[repr(i32)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum SigmaskHow {
@@ -240,7 +390,7 @@ impl SigSet {
     }
 
     pub fn add(&mut self, signal: Signal) {
-        unsafe { libc::sigaddset(&mut self.sigset as *mut libc::sigset_t, signal as libc::c_int) };
+        unsafe { libc::sigaddset(&mut self.sigset as *mut libc::sigset_t, signal.into()) };
     }
 
     pub fn clear(&mut self) {
@@ -248,17 +398,15 @@ impl SigSet {
     }
 
     pub fn remove(&mut self, signal: Signal) {
-        unsafe { libc::sigdelset(&mut self.sigset as *mut libc::sigset_t, signal as libc::c_int) };
+        unsafe { libc::sigdelset(&mut self.sigset as *mut libc::sigset_t, signal.into()) };
     }
 
     pub fn contains(&self, signal: Signal) -> bool {
-        let res = unsafe { libc::sigismember(&self.sigset as *const libc::sigset_t, signal as libc::c_int) };
+        let res = unsafe { libc::sigismember(&self.sigset as *const libc::sigset_t, signal.into()) };
 
-        match res {
-            1 => true,
-            0 => false,
-            _ => unreachable!("unexpected value from sigismember"),
-        }
+        // sigismember only fails for invalid signal numbers, which no
+        // `Signal` has
+        res == 1
     }
 
     pub fn extend(&mut self, other: &SigSet) {
@@ -267,6 +415,20 @@ impl SigSet {
                 self.add(signal);
             }
         }
+        self.extend_realtime(other);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn extend_realtime(&mut self, other: &SigSet) {
+        for signal in Signal::realtime_iterator() {
+            if other.contains(signal) {
+                self.add(signal);
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn extend_realtime(&mut self, _other: &SigSet) {
     }
 
     /// Gets the currently blocked (masked) set of signals for the calling thread.
@@ -306,6 +468,33 @@ impl SigSet {
 
         Errno::result(res).map(|_| Signal::from_c_int(signum).unwrap())
     }
+
+    /// Like `wait`, but returns the full information about the accepted signal
+    /// (see [sigwaitinfo(2)](http://man7.org/linux/man-pages/man2/sigwaitinfo.2.html)).
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn wait_info(&self) -> Result<SigInfo> {
+        let mut siginfo: libc::siginfo_t = unsafe { mem::uninitialized() };
+        let res = unsafe { ffi::sigwaitinfo(&self.sigset as *const libc::sigset_t, &mut siginfo) };
+
+        Errno::result(res).map(|_| SigInfo::from(&siginfo))
+    }
+
+    /// Like `wait_info`, but gives up after `timeout` has elapsed without any of
+    /// the signals becoming pending, in which case `None` is returned (see
+    /// [sigtimedwait(2)](http://man7.org/linux/man-pages/man2/sigtimedwait.2.html)).
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn timed_wait(&self, timeout: TimeSpec) -> Result<Option<SigInfo>> {
+        let mut siginfo: libc::siginfo_t = unsafe { mem::uninitialized() };
+        let res = unsafe {
+            ffi::sigtimedwait(&self.sigset as *const libc::sigset_t, &mut siginfo, timeout.as_ref())
+        };
+
+        match Errno::result(res) {
+            Ok(_) => Ok(Some(SigInfo::from(&siginfo))),
+            Err(Error::Sys(Errno::EAGAIN)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 impl AsRef<libc::sigset_t> for SigSet {
@@ -370,7 +559,7 @@ pub unsafe fn sigaction(signal: Signal, sigaction: &SigAction) -> Result<SigActi
     let mut oldact = mem::uninitialized::<libc::sigaction>();
 
     let res =
-        libc::sigaction(signal as libc::c_int, &sigaction.sigaction as *const libc::sigaction, &mut oldact as *mut libc::sigaction);
+        libc::sigaction(signal.into(), &sigaction.sigaction as *const libc::sigaction, &mut oldact as *mut libc::sigaction);
 
     Errno::result(res).map(|_| SigAction { sigaction: oldact })
 }
@@ -409,10 +598,192 @@ pub fn pthread_sigmask(how: SigmaskHow,
     Errno::result(res).map(drop)
 }
 
+/// A description of an alternate signal stack, on which handlers installed with
+/// `SA_ONSTACK` run (`stack_t`).
+#[derive(Clone, Copy)]
+pub struct SigStack {
+    stack: libc::stack_t,
+}
+
+impl SigStack {
+    /// Describes a stack occupying the `size` bytes starting at `base`.
+    pub fn new(base: *mut libc::c_void, size: usize, flags: SsFlags) -> SigStack {
+        let mut stack: libc::stack_t = unsafe { mem::zeroed() };
+        stack.ss_sp = base;
+        stack.ss_size = size as libc::size_t;
+        stack.ss_flags = flags.bits();
+
+        SigStack { stack: stack }
+    }
+
+    /// Describes no alternate stack; installing it disables the current one.
+    pub fn disabled() -> SigStack {
+        SigStack::new(ptr::null_mut(), 0, SS_DISABLE)
+    }
+
+    /// The lowest address of the stack.
+    pub fn base(&self) -> *mut libc::c_void {
+        self.stack.ss_sp
+    }
+
+    /// The size of the stack in bytes.
+    pub fn size(&self) -> usize {
+        self.stack.ss_size as usize
+    }
+
+    /// `SS_DISABLE` if there is no alternate stack, and `SS_ONSTACK` if the
+    /// calling thread is currently running on it.
+    pub fn flags(&self) -> SsFlags {
+        SsFlags::from_bits_truncate(self.stack.ss_flags)
+    }
+}
+
+impl AsRef<libc::stack_t> for SigStack {
+    fn as_ref(&self) -> &libc::stack_t {
+        &self.stack
+    }
+}
+
+impl Debug for SigStack {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SigStack")
+            .field("base", &self.base())
+            .field("size", &self.size())
+            .field("flags", &self.flags())
+            .finish()
+    }
+}
+
+/// Installs `stack` as the alternate signal stack of the calling thread, if
+/// given, and returns the previous one (see
+/// [sigaltstack(2)](http://man7.org/linux/man-pages/man2/sigaltstack.2.html)).
+///
+/// This is unsafe because the memory described by `stack` must remain valid
+/// and unused for anything else for as long as it is installed.  `SignalStack`
+/// takes care of that.
+pub unsafe fn sigaltstack(stack: Option<&SigStack>) -> Result<SigStack> {
+    let mut old: libc::stack_t = mem::uninitialized();
+    let res = libc::sigaltstack(stack.map_or(ptr::null(), |s| &s.stack as *const libc::stack_t),
+                                &mut old as *mut libc::stack_t);
+
+    Errno::result(res).map(|_| SigStack { stack: old })
+}
+
+/// An alternate signal stack owned by the calling thread.
+///
+/// The stack is allocated with `mmap`, with an inaccessible guard page below it
+/// so that overflowing it faults instead of corrupting other memory.  It stays
+/// installed until the `SignalStack` is dropped, which reinstalls the previous
+/// alternate stack and frees the memory.  If it is no longer the installed
+/// stack by then, or a signal handler is running on it, the memory is leaked
+/// rather than freed under someone's feet.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nix::sys::signal::*;
+///
+/// extern fn handler(_: nix::libc::c_int) {}
+///
+/// // Keep the stack alive for as long as the handler may run
+/// let _stack = SignalStack::new(64 * 1024).unwrap();
+/// let action = SigAction::new(SigHandler::Handler(handler), SA_ONSTACK, SigSet::empty());
+/// unsafe { sigaction(SIGSEGV, &action) }.unwrap();
+/// ```
+pub struct SignalStack {
+    mapping: *mut libc::c_void,
+    mapping_len: usize,
+    stack: SigStack,
+    previous: SigStack,
+}
+
+impl SignalStack {
+    /// Allocates a stack of at least `size` bytes and installs it for the
+    /// calling thread.
+    pub fn new(size: usize) -> Result<SignalStack> {
+        use sys::mman::{self, MAP_ANON, MAP_PRIVATE, PROT_NONE, PROT_READ, PROT_WRITE};
+
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let size = (size + page - 1) / page * page;
+        let mapping_len = size + page;
+
+        let mapping = try!(unsafe {
+            mman::mmap(ptr::null_mut(), mapping_len, PROT_READ | PROT_WRITE,
+                       MAP_PRIVATE | MAP_ANON, -1, 0)
+        });
+        // Stacks grow down, so the guard page goes at the bottom
+        let stack = SigStack::new(unsafe { (mapping as *mut u8).offset(page as isize) } as *mut libc::c_void,
+                                  size, SsFlags::empty());
+        let res = unsafe { mman::mprotect(mapping, page, PROT_NONE) }
+            .and_then(|_| unsafe { sigaltstack(Some(&stack)) });
+
+        match res {
+            Ok(previous) => Ok(SignalStack {
+                mapping: mapping,
+                mapping_len: mapping_len,
+                stack: stack,
+                previous: previous,
+            }),
+            Err(e) => {
+                let _ = unsafe { mman::munmap(mapping, mapping_len) };
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the alternate signal stack currently installed for the calling
+    /// thread.
+    pub fn current() -> Result<SigStack> {
+        unsafe { sigaltstack(None) }
+    }
+
+    /// The usable part of this stack, excluding the guard page.
+    pub fn stack(&self) -> SigStack {
+        self.stack
+    }
+}
+
+impl Drop for SignalStack {
+    fn drop(&mut self) {
+        // Only free the memory once it's certain that nothing uses it any
+        // more.  If another stack was installed since, or this one can't be
+        // uninstalled because a handler is running on it, leak it instead.
+        unsafe {
+            let installed = match sigaltstack(None) {
+                Ok(current) => current.base() == self.stack.base() &&
+                    !current.flags().contains(SS_ONSTACK),
+                Err(_) => false,
+            };
+            if installed && sigaltstack(Some(&self.previous)).is_ok() {
+                let _ = sys::mman::munmap(self.mapping, self.mapping_len);
+            }
+        }
+    }
+}
+
+impl Debug for SignalStack {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SignalStack")
+            .field("stack", &self.stack)
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+/// Returns the set of signals that are pending for delivery to the calling
+/// thread, because they were raised while blocked (see
+/// [sigpending(2)](http://man7.org/linux/man-pages/man2/sigpending.2.html)).
+pub fn sigpending() -> Result<SigSet> {
+    let mut sigset: libc::sigset_t = unsafe { mem::uninitialized() };
+    let res = unsafe { libc::sigpending(&mut sigset as *mut libc::sigset_t) };
+
+    Errno::result(res).map(|_| SigSet { sigset: sigset })
+}
+
 pub fn kill<T: Into<Option<Signal>>>(pid: libc::pid_t, signal: T) -> Result<()> {
     let res = unsafe { libc::kill(pid,
                                   match signal.into() {
-                                      Some(s) => s as libc::c_int,
+                                      Some(s) => s.into(),
                                       None => 0,
                                   }) };
 
@@ -420,11 +791,447 @@ pub fn kill<T: Into<Option<Signal>>>(pid: libc::pid_t, signal: T) -> Result<()>
 }
 
 pub fn raise(signal: Signal) -> Result<()> {
-    let res = unsafe { libc::raise(signal as libc::c_int) };
+    let res = unsafe { libc::raise(signal.into()) };
 
     Errno::result(res).map(drop)
 }
 
+/// Sends `signal` to the process `pid` together with the payload `value`, which
+/// the receiver finds in the `si_value` field of its `siginfo_t` (see
+/// [sigqueue(3)](http://man7.org/linux/man-pages/man3/sigqueue.3.html)).
+///
+/// Unlike standard signals, multiple instances of a real-time signal queued
+/// this way are all delivered.
+// As with SigevNotify, the sigval union is presented as an intptr_t.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn sigqueue(pid: libc::pid_t, signal: Signal, value: libc::intptr_t) -> Result<()> {
+    let sigval = libc::sigval { sival_ptr: value as *mut libc::c_void };
+    let res = unsafe { ffi::sigqueue(pid, signal.into(), sigval) };
+
+    Errno::result(res).map(drop)
+}
+
+
+/// Generic `si_code` values, which say how a signal was sent regardless of
+/// which signal it is, and the signal specific values, from `<signal.h>`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod si_code {
+    use libc::c_int;
+
+    pub const SI_USER: c_int = 0;
+    pub const SI_KERNEL: c_int = 0x80;
+    pub const SI_QUEUE: c_int = -1;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    pub const SI_TIMER: c_int = -2;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    pub const SI_MESGQ: c_int = -3;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    pub const SI_ASYNCIO: c_int = -4;
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    pub const SI_TIMER: c_int = -3;
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    pub const SI_MESGQ: c_int = -4;
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    pub const SI_ASYNCIO: c_int = -2;
+    pub const SI_SIGIO: c_int = -5;
+    pub const SI_TKILL: c_int = -6;
+}
+
+// The parts of siginfo_t that we decode, as laid out by Linux.  libc only
+// exposes the first three fields, because the rest is a union whose active
+// member depends on the signal and si_code.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_header {
+    si_signo: libc::c_int,
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    si_errno: libc::c_int,
+    si_code: libc::c_int,
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    si_errno: libc::c_int,
+    // The union is pointer aligned
+    _align: [usize; 0],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_rt {
+    header: siginfo_header,
+    si_pid: libc::pid_t,
+    si_uid: libc::uid_t,
+    si_value: libc::intptr_t,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_timer {
+    header: siginfo_header,
+    si_tid: libc::c_int,
+    si_overrun: libc::c_int,
+    si_value: libc::intptr_t,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_sigchld {
+    header: siginfo_header,
+    si_pid: libc::pid_t,
+    si_uid: libc::uid_t,
+    si_status: libc::c_int,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_sigfault {
+    header: siginfo_header,
+    si_addr: usize,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+struct siginfo_sigpoll {
+    header: siginfo_header,
+    si_band: libc::c_long,
+    si_fd: libc::c_int,
+}
+
+/// Declares a signal specific `si_code` enum together with its decoding
+/// function.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+macro_rules! si_code_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$vattr:meta])* $variant:ident = $value:expr),+
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum $name {
+            $($(#[$vattr])* $variant),+
+        }
+
+        impl $name {
+            fn from_c_int(code: libc::c_int) -> Option<$name> {
+                $(if code == $value { return Some($name::$variant); })+
+                None
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGILL` was generated by the kernel.
+    pub enum IllCode {
+        /// Illegal opcode (`ILL_ILLOPC`)
+        IllOpc = 1,
+        /// Illegal operand (`ILL_ILLOPN`)
+        IllOpn = 2,
+        /// Illegal addressing mode (`ILL_ILLADR`)
+        IllAdr = 3,
+        /// Illegal trap (`ILL_ILLTRP`)
+        IllTrp = 4,
+        /// Privileged opcode (`ILL_PRVOPC`)
+        PrvOpc = 5,
+        /// Privileged register (`ILL_PRVREG`)
+        PrvReg = 6,
+        /// Coprocessor error (`ILL_COPROC`)
+        Coproc = 7,
+        /// Internal stack error (`ILL_BADSTK`)
+        BadStk = 8
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGFPE` was generated by the kernel.
+    pub enum FpeCode {
+        /// Integer divide by zero (`FPE_INTDIV`)
+        IntDiv = 1,
+        /// Integer overflow (`FPE_INTOVF`)
+        IntOvf = 2,
+        /// Floating point divide by zero (`FPE_FLTDIV`)
+        FltDiv = 3,
+        /// Floating point overflow (`FPE_FLTOVF`)
+        FltOvf = 4,
+        /// Floating point underflow (`FPE_FLTUND`)
+        FltUnd = 5,
+        /// Floating point inexact result (`FPE_FLTRES`)
+        FltRes = 6,
+        /// Floating point invalid operation (`FPE_FLTINV`)
+        FltInv = 7,
+        /// Subscript out of range (`FPE_FLTSUB`)
+        FltSub = 8
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGSEGV` was generated by the kernel.
+    pub enum SegvCode {
+        /// Address not mapped to object (`SEGV_MAPERR`)
+        MapErr = 1,
+        /// Invalid permissions for mapped object (`SEGV_ACCERR`)
+        AccErr = 2
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGBUS` was generated by the kernel.
+    pub enum BusCode {
+        /// Invalid address alignment (`BUS_ADRALN`)
+        AdrAln = 1,
+        /// Nonexistent physical address (`BUS_ADRERR`)
+        AdrErr = 2,
+        /// Object specific hardware error (`BUS_OBJERR`)
+        ObjErr = 3
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGTRAP` was generated by the kernel.
+    pub enum TrapCode {
+        /// Process breakpoint (`TRAP_BRKPT`)
+        Brkpt = 1,
+        /// Process trace trap (`TRAP_TRACE`)
+        Trace = 2,
+        /// Process taken branch trap (`TRAP_BRANCH`)
+        Branch = 3,
+        /// Hardware breakpoint or watchpoint (`TRAP_HWBKPT`)
+        HwBkpt = 4
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGCHLD` was sent.
+    pub enum ChildCode {
+        /// The child has exited (`CLD_EXITED`)
+        Exited = 1,
+        /// The child was killed (`CLD_KILLED`)
+        Killed = 2,
+        /// The child was killed and dumped core (`CLD_DUMPED`)
+        Dumped = 3,
+        /// The traced child has trapped (`CLD_TRAPPED`)
+        Trapped = 4,
+        /// The child has stopped (`CLD_STOPPED`)
+        Stopped = 5,
+        /// The stopped child has continued (`CLD_CONTINUED`)
+        Continued = 6
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+si_code_enum! {
+    /// Why a `SIGPOLL`/`SIGIO` was generated by the kernel.
+    pub enum PollCode {
+        /// Data input available (`POLL_IN`)
+        In = 1,
+        /// Output buffers available (`POLL_OUT`)
+        Out = 2,
+        /// Input message available (`POLL_MSG`)
+        Msg = 3,
+        /// I/O error (`POLL_ERR`)
+        Err = 4,
+        /// High priority input available (`POLL_PRI`)
+        Pri = 5,
+        /// Device disconnected (`POLL_HUP`)
+        Hup = 6
+    }
+}
+
+/// How a signal was generated, along with the fields of the `siginfo_t` which
+/// are valid in that case.
+///
+/// The sigqueue values are presented as `intptr_t`, as in `SigevNotify`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SigCode {
+    /// Sent by `kill` or `raise` (`SI_USER`).
+    User { pid: libc::pid_t, uid: libc::uid_t },
+    /// Sent by the kernel for a reason not covered by the signal specific
+    /// codes (`SI_KERNEL`).
+    Kernel,
+    /// Sent by `sigqueue` (`SI_QUEUE`).
+    Queue { pid: libc::pid_t, uid: libc::uid_t, value: libc::intptr_t },
+    /// A POSIX timer expired (`SI_TIMER`).
+    Timer { overrun: libc::c_int, value: libc::intptr_t },
+    /// A message arrived on an empty POSIX message queue (`SI_MESGQ`).
+    MesgQ { pid: libc::pid_t, uid: libc::uid_t, value: libc::intptr_t },
+    /// An asynchronous I/O request completed (`SI_ASYNCIO`).
+    AsyncIo { value: libc::intptr_t },
+    /// Queued by the kernel for I/O readiness (`SI_SIGIO`).
+    SigIo { band: libc::c_long, fd: libc::c_int },
+    /// Sent by `tkill` or `tgkill` (`SI_TKILL`).
+    Tkill { pid: libc::pid_t, uid: libc::uid_t },
+    /// An illegal instruction at `addr`.
+    Ill { code: IllCode, addr: usize },
+    /// An arithmetic exception at `addr`.
+    Fpe { code: FpeCode, addr: usize },
+    /// An invalid memory reference to `addr`.
+    Segv { code: SegvCode, addr: usize },
+    /// A bus error at `addr`.
+    Bus { code: BusCode, addr: usize },
+    /// A trace or breakpoint trap.
+    Trap { code: TrapCode, addr: usize },
+    /// A child changed state.  `status` is the exit status for
+    /// `ChildCode::Exited`, and the signal number otherwise.
+    Child { code: ChildCode, pid: libc::pid_t, uid: libc::uid_t, status: libc::c_int },
+    /// An I/O event on `fd`.
+    Poll { code: PollCode, band: libc::c_long, fd: libc::c_int },
+    /// An `si_code` that nix does not know how to decode.
+    Unknown(libc::c_int),
+}
+
+/// A decoded `siginfo_t`, as passed to `SigHandler::SigAction` handlers, or a
+/// `signalfd_siginfo`, as read from a `SignalFd`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nix::libc;
+/// use nix::sys::signal::{SigCode, SigInfo, SegvCode};
+///
+/// extern fn handler(_: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+///     let info = SigInfo::from(unsafe { &*info });
+///     if let SigCode::Segv { code: SegvCode::MapErr, addr } = info.code() {
+///         // `addr` was not mapped
+///     }
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SigInfo {
+    signo: libc::c_int,
+    errno: libc::c_int,
+    code: SigCode,
+}
+
+// The union fields of siginfo_t and signalfd_siginfo that SigInfo cares about,
+// which are decoded the same way for both.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+struct SigInfoFields {
+    signo: libc::c_int,
+    errno: libc::c_int,
+    code: libc::c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    status: libc::c_int,
+    overrun: libc::c_int,
+    value: libc::intptr_t,
+    addr: usize,
+    band: libc::c_long,
+    fd: libc::c_int,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SigInfo {
+    fn decode(f: SigInfoFields) -> SigInfo {
+        use self::si_code::*;
+
+        let code = match f.code {
+            SI_USER => SigCode::User { pid: f.pid, uid: f.uid },
+            SI_KERNEL => SigCode::Kernel,
+            SI_QUEUE => SigCode::Queue { pid: f.pid, uid: f.uid, value: f.value },
+            SI_TIMER => SigCode::Timer { overrun: f.overrun, value: f.value },
+            SI_MESGQ => SigCode::MesgQ { pid: f.pid, uid: f.uid, value: f.value },
+            SI_ASYNCIO => SigCode::AsyncIo { value: f.value },
+            SI_SIGIO => SigCode::SigIo { band: f.band, fd: f.fd },
+            SI_TKILL => SigCode::Tkill { pid: f.pid, uid: f.uid },
+            code => {
+                let decoded = match f.signo {
+                    libc::SIGILL => IllCode::from_c_int(code).map(|c| SigCode::Ill { code: c, addr: f.addr }),
+                    libc::SIGFPE => FpeCode::from_c_int(code).map(|c| SigCode::Fpe { code: c, addr: f.addr }),
+                    libc::SIGSEGV => SegvCode::from_c_int(code).map(|c| SigCode::Segv { code: c, addr: f.addr }),
+                    libc::SIGBUS => BusCode::from_c_int(code).map(|c| SigCode::Bus { code: c, addr: f.addr }),
+                    libc::SIGTRAP => TrapCode::from_c_int(code).map(|c| SigCode::Trap { code: c, addr: f.addr }),
+                    libc::SIGCHLD => ChildCode::from_c_int(code).map(|c| SigCode::Child {
+                        code: c, pid: f.pid, uid: f.uid, status: f.status
+                    }),
+                    libc::SIGIO => PollCode::from_c_int(code).map(|c| SigCode::Poll { code: c, band: f.band, fd: f.fd }),
+                    _ => None,
+                };
+                decoded.unwrap_or(SigCode::Unknown(code))
+            }
+        };
+
+        SigInfo { signo: f.signo, errno: f.errno, code: code }
+    }
+
+    /// The signal that was delivered (`si_signo`).  Fails with `EINVAL` if
+    /// the raw signal number is not a valid `Signal`.
+    pub fn signal(&self) -> Result<Signal> {
+        Signal::from_c_int(self.signo)
+    }
+
+    /// The raw signal number that was delivered (`si_signo`).
+    pub fn signo(&self) -> libc::c_int {
+        self.signo
+    }
+
+    /// An errno value associated with the signal, usually 0 (`si_errno`).
+    pub fn errno(&self) -> libc::c_int {
+        self.errno
+    }
+
+    /// How the signal was generated, with the fields that are valid for it.
+    pub fn code(&self) -> SigCode {
+        self.code
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> From<&'a libc::siginfo_t> for SigInfo {
+    fn from(siginfo: &libc::siginfo_t) -> Self {
+        let raw = siginfo as *const libc::siginfo_t;
+        // All of the views fit within siginfo_t, and it is fine to read the
+        // inactive ones because siginfo_t is plain data.
+        let (header, rt, timer, sigchld, sigfault, sigpoll) = unsafe {
+            (&*(raw as *const siginfo_header),
+             &*(raw as *const siginfo_rt),
+             &*(raw as *const siginfo_timer),
+             &*(raw as *const siginfo_sigchld),
+             &*(raw as *const siginfo_sigfault),
+             &*(raw as *const siginfo_sigpoll))
+        };
+
+        SigInfo::decode(SigInfoFields {
+            signo: header.si_signo,
+            errno: header.si_errno,
+            code: header.si_code,
+            pid: rt.si_pid,
+            uid: rt.si_uid,
+            status: sigchld.si_status,
+            overrun: timer.si_overrun,
+            value: rt.si_value,
+            addr: sigfault.si_addr,
+            band: sigpoll.si_band,
+            fd: sigpoll.si_fd,
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> From<&'a libc::signalfd_siginfo> for SigInfo {
+    fn from(siginfo: &libc::signalfd_siginfo) -> Self {
+        SigInfo::decode(SigInfoFields {
+            signo: siginfo.ssi_signo as libc::c_int,
+            errno: siginfo.ssi_errno,
+            code: siginfo.ssi_code,
+            pid: siginfo.ssi_pid as libc::pid_t,
+            uid: siginfo.ssi_uid,
+            status: siginfo.ssi_status,
+            overrun: siginfo.ssi_overrun as libc::c_int,
+            value: siginfo.ssi_ptr as libc::intptr_t,
+            addr: siginfo.ssi_addr as usize,
+            band: siginfo.ssi_band as libc::c_long,
+            fd: siginfo.ssi_fd,
+        })
+    }
+}
 
 #[cfg(target_os = "freebsd")]
 pub type type_of_thread_id = libc::lwpid_t;
@@ -493,11 +1300,11 @@ impl SigEvent {
             SigevNotify::SigevThreadId{..} => 4  // No SIGEV_THREAD_ID defined
         };
         sev.sigev_signo = match sigev_notify {
-            SigevNotify::SigevSignal{ signal, .. } => signal as ::c_int,
+            SigevNotify::SigevSignal{ signal, .. } => signal.into(),
             #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
             SigevNotify::SigevKevent{ kq, ..} => kq,
             #[cfg(any(target_os = "linux", target_os = "freebsd"))]
-            SigevNotify::SigevThreadId{ signal, .. } => signal as ::c_int,
+            SigevNotify::SigevThreadId{ signal, .. } => signal.into(),
             _ => 0
         };
         sev.sigev_value.sival_ptr = match sigev_notify {
@@ -692,6 +1499,122 @@ mod tests {
         assert_eq!(action_ign.handler(), SigHandler::SigIgn);
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_extend_realtime() {
+        let mut one_signal = SigSet::empty();
+        one_signal.add(Signal::realtime(2).unwrap());
+
+        let mut two_signals = SigSet::empty();
+        two_signals.add(SIGUSR2);
+        two_signals.extend(&one_signal);
+
+        assert!(two_signals.contains(Signal::realtime(2).unwrap()));
+        assert!(two_signals.contains(SIGUSR2));
+    }
+
+    #[test]
+    fn test_from_c_int() {
+        for signal in Signal::iterator() {
+            assert_eq!(Signal::from_c_int(signal.into()), Ok(signal));
+        }
+        assert!(Signal::from_c_int(0).is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_siginfo_from_signalfd_siginfo() {
+        use libc;
+        use std::mem;
+
+        let mut raw: libc::signalfd_siginfo = unsafe { mem::zeroed() };
+        raw.ssi_signo = libc::SIGSEGV as u32;
+        raw.ssi_code = 1;
+        raw.ssi_addr = 0x1000;
+        let info = SigInfo::from(&raw);
+        assert_eq!(info.signal(), Ok(SIGSEGV));
+        assert_eq!(info.code(), SigCode::Segv { code: SegvCode::MapErr, addr: 0x1000 });
+
+        raw.ssi_signo = libc::SIGCHLD as u32;
+        raw.ssi_code = 2;
+        raw.ssi_pid = 42;
+        raw.ssi_uid = 1000;
+        raw.ssi_status = libc::SIGKILL;
+        let info = SigInfo::from(&raw);
+        assert_eq!(info.code(), SigCode::Child { code: ChildCode::Killed, pid: 42, uid: 1000,
+                                                 status: libc::SIGKILL });
+
+        // Generic codes take precedence over the signal specific ones
+        raw.ssi_code = -1;
+        raw.ssi_ptr = 7;
+        let info = SigInfo::from(&raw);
+        assert_eq!(info.code(), SigCode::Queue { pid: 42, uid: 1000, value: 7 });
+
+        raw.ssi_code = 99;
+        assert_eq!(SigInfo::from(&raw).code(), SigCode::Unknown(99));
+
+        // A signal number nix doesn't know is kept rather than rejected
+        raw.ssi_signo = 0;
+        let info = SigInfo::from(&raw);
+        assert_eq!(info.signo(), 0);
+        assert!(info.signal().is_err());
+        assert_eq!(info.code(), SigCode::Unknown(99));
+    }
+
+    #[test]
+    fn test_sigpending() {
+        let mut mask = SigSet::empty();
+        mask.add(SIGUSR2);
+        let old_mask = mask.thread_swap_mask(SigmaskHow::SIG_BLOCK).unwrap();
+
+        raise(SIGUSR2).unwrap();
+        assert!(sigpending().unwrap().contains(SIGUSR2));
+        assert_eq!(mask.wait().unwrap(), SIGUSR2);
+        assert!(!sigpending().unwrap().contains(SIGUSR2));
+
+        // The harness reuses threads, so don't leave SIGUSR2 blocked
+        old_mask.thread_set_mask().unwrap();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_timed_wait() {
+        use sys::time::{TimeSpec, TimeValLike};
+        use sys::wait::{waitpid, WaitStatus};
+        use unistd::{fork, getpid, getuid};
+        use unistd::ForkResult::*;
+
+        let signal = Signal::realtime(3).unwrap();
+        let mut mask = SigSet::empty();
+        mask.add(signal);
+
+        // sigqueue is process-directed, so send it in a child, where it can't
+        // be delivered to another test's thread
+        match fork() {
+            Ok(Child) => {
+                let queued = |info: ::Result<Option<SigInfo>>| match info {
+                    Ok(Some(info)) => info.signal() == Ok(signal) &&
+                        info.code() == SigCode::Queue { pid: getpid(), uid: getuid(), value: 1234 },
+                    _ => false,
+                };
+                let received = mask.thread_block().is_ok() &&
+                    mask.timed_wait(TimeSpec::milliseconds(10)) == Ok(None) &&
+                    sigqueue(getpid(), signal, 1234).is_ok() &&
+                    queued(mask.timed_wait(TimeSpec::seconds(10)));
+                unsafe { ::libc::_exit(if received { 0 } else { 1 }) }
+            },
+            Ok(Parent { child }) => {
+                assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            },
+            Err(_) => panic!("Error: Fork Failed")
+        }
+
+        mask.thread_block().unwrap();
+        raise(signal).unwrap();
+        let info = mask.wait_info().unwrap();
+        assert_eq!(info.signal(), Ok(signal));
+    }
+
     // TODO(#251): Re-enable after figuring out flakiness.
     #[cfg(not(any(target_os = "macos", target_os = "ios")))]
     #[test]
@@ -63,7 +63,7 @@ pub fn signalfd(fd: RawFd, mask: &SigSet, flags: SfdFlags) -> Result<RawFd> {
 /// use nix::sys::signalfd::*;
 ///
 /// let mut mask = SigSet::empty();
-/// mask.add(signal::SIGUSR1).unwrap();
+/// mask.add(signal::SIGUSR1);
 ///
 /// // Block the signal, otherwise the default handler will be invoked instead.
 /// mask.thread_block().unwrap();
@@ -72,8 +72,10 @@ pub fn signalfd(fd: RawFd, mask: &SigSet, flags: SfdFlags) -> Result<RawFd> {
 /// let mut sfd = SignalFd::with_flags(&mask, SFD_NONBLOCK).unwrap();
 ///
 /// match sfd.read_signal() {
-///     // we caught a signal
-///     Ok(Some(sig)) => (),
+///     // we caught a signal, which can be decoded with `SigInfo`
+///     Ok(Some(sig)) => {
+///         let info = signal::SigInfo::from(&sig);
+///     },
 ///
 ///     // there were no signals waiting (only happens when the SFD_NONBLOCK flag is set,
 ///     // otherwise the read_signal call blocks)
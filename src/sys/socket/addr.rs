@@ -1,12 +1,14 @@
 use super::{consts, sa_family_t};
 use {Errno, Error, Result, NixPath};
 use libc;
-use std::{fmt, hash, mem, net, ptr};
+use std::{cmp, fmt, hash, mem, net, ptr, slice};
 use std::ffi::OsStr;
 use std::path::Path;
 use std::os::unix::ffi::OsStrExt;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 use ::sys::socket::addr::netlink::NetlinkAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use ::sys::socket::addr::link::{LinkAddr, sockaddr_ll};
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 use std::os::unix::io::RawFd;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -482,6 +484,8 @@ pub enum SockAddr {
     Unix(UnixAddr),
     #[cfg(any(target_os = "linux", target_os = "android"))]
     Netlink(NetlinkAddr),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Link(LinkAddr),
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     SysControl(SysControlAddr),
 }
@@ -500,6 +504,11 @@ impl SockAddr {
         SockAddr::Netlink(NetlinkAddr::new(pid, groups))
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn new_link(addr: LinkAddr) -> SockAddr {
+        SockAddr::Link(addr)
+    }
+
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     pub fn new_sys_control(sockfd: RawFd, name: &str, unit: u32) -> Result<SockAddr> {
         SysControlAddr::from_name(sockfd, name, unit).map(|a| SockAddr::SysControl(a))
@@ -512,6 +521,8 @@ impl SockAddr {
             SockAddr::Unix(..) => AddressFamily::Unix,
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(..) => AddressFamily::Netlink,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Link(..) => AddressFamily::Packet,
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(..) => AddressFamily::System,
         }
@@ -528,6 +539,8 @@ impl SockAddr {
             SockAddr::Unix(UnixAddr(ref addr, len)) => (mem::transmute(addr), (len + offset_of!(libc::sockaddr_un, sun_path)) as libc::socklen_t),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(NetlinkAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Link(LinkAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<sockaddr_ll>() as libc::socklen_t),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(SysControlAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<sys_control::sockaddr_ctl>() as libc::socklen_t),
         }
@@ -547,6 +560,10 @@ impl PartialEq for SockAddr {
             (SockAddr::Netlink(ref a), SockAddr::Netlink(ref b)) => {
                 a == b
             }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (SockAddr::Link(ref a), SockAddr::Link(ref b)) => {
+                a == b
+            }
             _ => false,
         }
     }
@@ -562,6 +579,8 @@ impl hash::Hash for SockAddr {
             SockAddr::Unix(ref a) => a.hash(s),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(ref a) => a.hash(s),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Link(ref a) => a.hash(s),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(ref a) => a.hash(s),
         }
@@ -581,12 +600,193 @@ impl fmt::Display for SockAddr {
             SockAddr::Unix(ref unix) => unix.fmt(f),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             SockAddr::Netlink(ref nl) => nl.fmt(f),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            SockAddr::Link(ref ll) => ll.fmt(f),
             #[cfg(any(target_os = "macos", target_os = "ios"))]
             SockAddr::SysControl(ref sc) => sc.fmt(f),
         }
     }
 }
 
+/*
+ *
+ * ===== Sockaddr storage =====
+ *
+ */
+
+/// An owned socket address of any family, as filled in by the kernel.
+///
+/// Unlike `SockAddr`, this keeps the raw `sockaddr_storage` together with
+/// the length the kernel reported, so it can hold addresses of families
+/// nix doesn't know about, as well as unnamed and abstract Unix addresses
+/// whose meaning depends on that length.  Conversion to the typed
+/// addresses happens on demand.
+#[derive(Copy)]
+pub struct SockaddrStorage {
+    ss: libc::sockaddr_storage,
+    len: libc::socklen_t,
+}
+
+impl SockaddrStorage {
+    /// Wrap a `sockaddr_storage` of which the first `len` bytes are valid.
+    /// If `len` doesn't fit the address family, the conversions to typed
+    /// addresses fail with `EINVAL`.
+    pub fn from_raw(ss: libc::sockaddr_storage, len: libc::socklen_t) -> SockaddrStorage {
+        let len = cmp::min(len as usize, mem::size_of::<libc::sockaddr_storage>());
+        SockaddrStorage {
+            ss: ss,
+            len: len as libc::socklen_t,
+        }
+    }
+
+    /// The number of valid bytes in the address.
+    pub fn len(&self) -> libc::socklen_t {
+        self.len
+    }
+
+    /// Returns true if the kernel reported no address at all, as for an
+    /// unconnected datagram socket.
+    pub fn is_empty(&self) -> bool {
+        (self.len as usize) < mem::size_of::<sa_family_t>()
+    }
+
+    /// The raw address family, or `None` if the address is empty.
+    pub fn raw_family(&self) -> Option<sa_family_t> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.ss.ss_family)
+        }
+    }
+
+    /// The address family, or `None` if the address is empty or its family
+    /// is not one of `AddressFamily`.
+    pub fn family(&self) -> Option<AddressFamily> {
+        match self.raw_family().map(|af| af as i32) {
+            Some(consts::AF_UNIX) => Some(AddressFamily::Unix),
+            Some(consts::AF_INET) => Some(AddressFamily::Inet),
+            Some(consts::AF_INET6) => Some(AddressFamily::Inet6),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Some(consts::AF_NETLINK) => Some(AddressFamily::Netlink),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Some(consts::AF_PACKET) => Some(AddressFamily::Packet),
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            Some(consts::AF_SYSTEM) => Some(AddressFamily::System),
+            _ => None,
+        }
+    }
+
+    /// The valid bytes of the address.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(&self.ss as *const _ as *const u8,
+                                  self.len as usize)
+        }
+    }
+
+    /// Convert to a `SockAddr`.  Fails with `ENOTCONN` if the address is
+    /// empty, with `EAFNOSUPPORT` if `SockAddr` can't represent its family
+    /// and with `EINVAL` if the length doesn't fit the family.
+    pub fn to_sock_addr(&self) -> Result<SockAddr> {
+        unsafe { super::sockaddr_storage_to_addr(&self.ss, self.len as usize) }
+    }
+
+    /// Convert to an `InetAddr`, if this is an IPv4 or IPv6 address.
+    pub fn as_inet(&self) -> Option<InetAddr> {
+        match self.to_sock_addr() {
+            Ok(SockAddr::Inet(addr)) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// Convert to a `UnixAddr`, if this is a Unix address.
+    pub fn as_unix(&self) -> Option<UnixAddr> {
+        match self.to_sock_addr() {
+            Ok(SockAddr::Unix(addr)) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// Convert to a `LinkAddr`, if this is a link-layer address.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn as_link(&self) -> Option<LinkAddr> {
+        match self.to_sock_addr() {
+            Ok(SockAddr::Link(addr)) => Some(addr),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("{}", self)
+    }
+
+    pub fn as_ffi_pair(&self) -> (&libc::sockaddr, libc::socklen_t) {
+        unsafe { (mem::transmute(&self.ss), self.len) }
+    }
+}
+
+impl AsRef<libc::sockaddr_storage> for SockaddrStorage {
+    fn as_ref(&self) -> &libc::sockaddr_storage {
+        &self.ss
+    }
+}
+
+impl From<SockAddr> for SockaddrStorage {
+    fn from(addr: SockAddr) -> SockaddrStorage {
+        unsafe {
+            let mut ss: libc::sockaddr_storage = mem::zeroed();
+            let (sa, len) = addr.as_ffi_pair();
+            ptr::copy_nonoverlapping(sa as *const _ as *const u8,
+                                     &mut ss as *mut _ as *mut u8,
+                                     len as usize);
+            SockaddrStorage::from_raw(ss, len)
+        }
+    }
+}
+
+impl PartialEq for SockaddrStorage {
+    fn eq(&self, other: &SockaddrStorage) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SockaddrStorage {
+}
+
+impl hash::Hash for SockaddrStorage {
+    fn hash<H: hash::Hasher>(&self, s: &mut H) {
+        self.as_bytes().hash(s)
+    }
+}
+
+impl Clone for SockaddrStorage {
+    fn clone(&self) -> SockaddrStorage {
+        *self
+    }
+}
+
+impl fmt::Display for SockaddrStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to_sock_addr() {
+            Ok(addr) => addr.fmt(f),
+            Err(_) => match self.raw_family() {
+                Some(af) => write!(f, "<address family {}>", af),
+                None => f.write_str("<no address>"),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for SockaddrStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SockaddrStorage")
+            .field("family", &self.raw_family())
+            .field("len", &self.len)
+            .field("addr", &self.to_str())
+            .finish()
+    }
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub mod netlink {
     use ::sys::socket::addr::{AddressFamily};
@@ -642,6 +842,111 @@ pub mod netlink {
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod link {
+    use ::sys::socket::addr::{AddressFamily};
+    use libc::{c_int, c_uchar, c_ushort};
+    use std::{fmt, mem};
+    use std::hash::{Hash, Hasher};
+    use {Errno, Error, Result};
+
+    /// The link-layer socket address of a packet socket.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man7/packet.7.html)
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    pub struct sockaddr_ll {
+        pub sll_family: c_ushort,
+        pub sll_protocol: c_ushort,
+        pub sll_ifindex: c_int,
+        pub sll_hatype: c_ushort,
+        pub sll_pkttype: c_uchar,
+        pub sll_halen: c_uchar,
+        pub sll_addr: [c_uchar; 8],
+    }
+
+    /// A wrapper around `sockaddr_ll`, the address of a packet socket.
+    #[derive(Copy, Clone)]
+    pub struct LinkAddr(pub sockaddr_ll);
+
+    impl PartialEq for LinkAddr {
+        fn eq(&self, other: &Self) -> bool {
+            (self.protocol(), self.ifindex(), self.hatype(), self.pkttype(), self.addr()) ==
+            (other.protocol(), other.ifindex(), other.hatype(), other.pkttype(), other.addr())
+        }
+    }
+
+    impl Eq for LinkAddr {}
+
+    impl Hash for LinkAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            (self.protocol(), self.ifindex(), self.hatype(), self.pkttype(), self.addr()).hash(s);
+        }
+    }
+
+    impl LinkAddr {
+        /// Create an address for `bind` or `sendto`.  `protocol` is the
+        /// Ethernet protocol in host byte order (e.g. `ETH_P_ALL`),
+        /// `ifindex` the interface index, or 0 for any interface, and
+        /// `addr` the destination hardware address, which may be empty
+        /// when binding.  Fails with `EINVAL` if `addr` is longer than 8
+        /// bytes.
+        pub fn new(protocol: u16, ifindex: c_int, addr: &[u8]) -> Result<LinkAddr> {
+            let mut sa: sockaddr_ll = unsafe { mem::zeroed() };
+            if addr.len() > sa.sll_addr.len() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            sa.sll_family = AddressFamily::Packet as c_ushort;
+            sa.sll_protocol = protocol.to_be();
+            sa.sll_ifindex = ifindex;
+            sa.sll_halen = addr.len() as c_uchar;
+            sa.sll_addr[..addr.len()].copy_from_slice(addr);
+
+            Ok(LinkAddr(sa))
+        }
+
+        /// The Ethernet protocol, in host byte order.
+        pub fn protocol(&self) -> u16 {
+            u16::from_be(self.0.sll_protocol)
+        }
+
+        /// The interface index.
+        pub fn ifindex(&self) -> c_int {
+            self.0.sll_ifindex
+        }
+
+        /// The ARP hardware type, one of the `ARPHRD_*` constants.
+        pub fn hatype(&self) -> u16 {
+            self.0.sll_hatype
+        }
+
+        /// The packet type, one of the `PACKET_*` packet type constants.
+        pub fn pkttype(&self) -> u8 {
+            self.0.sll_pkttype
+        }
+
+        /// The hardware address.
+        pub fn addr(&self) -> &[u8] {
+            let len = ::std::cmp::min(self.0.sll_halen as usize, self.0.sll_addr.len());
+            &self.0.sll_addr[..len]
+        }
+    }
+
+    impl fmt::Display for LinkAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            try!(write!(f, "ifindex: {} protocol: {:#06x} addr: ",
+                        self.ifindex(), self.protocol()));
+            for (i, byte) in self.addr().iter().enumerate() {
+                if i > 0 {
+                    try!(f.write_str(":"));
+                }
+                try!(write!(f, "{:02x}", byte));
+            }
+            Ok(())
+        }
+    }
+}
+
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub mod sys_control {
     use ::sys::socket::consts;
@@ -0,0 +1,516 @@
+//! Classic BPF socket filters.
+//!
+//! A filter is a program of `SockFilter` instructions, run by the kernel on
+//! every packet arriving at the socket it is attached to.  It returns the
+//! number of bytes of the packet to keep, and zero to drop it.
+//!
+//! Programs can be written with `bpf_stmt` and `bpf_jump` like in C, or
+//! with `FilterBuilder`, which resolves jump targets from labels.  `run`
+//! interprets a program in userspace, to test it against sample packets.
+//!
+//! [Further reading](https://www.kernel.org/doc/Documentation/networking/filter.txt)
+
+use {Errno, Error, Result};
+use libc::c_ushort;
+use std::os::unix::io::RawFd;
+use super::{setsockopt, sockopt, sock_filter, sock_fprog};
+
+/// A classic BPF instruction.
+pub type SockFilter = sock_filter;
+
+// Instruction classes
+pub const BPF_LD: u16 = 0x00;
+pub const BPF_LDX: u16 = 0x01;
+pub const BPF_ST: u16 = 0x02;
+pub const BPF_STX: u16 = 0x03;
+pub const BPF_ALU: u16 = 0x04;
+pub const BPF_JMP: u16 = 0x05;
+pub const BPF_RET: u16 = 0x06;
+pub const BPF_MISC: u16 = 0x07;
+
+// Sizes of BPF_LD loads
+pub const BPF_W: u16 = 0x00;
+pub const BPF_H: u16 = 0x08;
+pub const BPF_B: u16 = 0x10;
+
+// Modes of BPF_LD and BPF_LDX loads
+pub const BPF_IMM: u16 = 0x00;
+pub const BPF_ABS: u16 = 0x20;
+pub const BPF_IND: u16 = 0x40;
+pub const BPF_MEM: u16 = 0x60;
+pub const BPF_LEN: u16 = 0x80;
+pub const BPF_MSH: u16 = 0xa0;
+
+// BPF_ALU operations
+pub const BPF_ADD: u16 = 0x00;
+pub const BPF_SUB: u16 = 0x10;
+pub const BPF_MUL: u16 = 0x20;
+pub const BPF_DIV: u16 = 0x30;
+pub const BPF_OR: u16 = 0x40;
+pub const BPF_AND: u16 = 0x50;
+pub const BPF_LSH: u16 = 0x60;
+pub const BPF_RSH: u16 = 0x70;
+pub const BPF_NEG: u16 = 0x80;
+pub const BPF_MOD: u16 = 0x90;
+pub const BPF_XOR: u16 = 0xa0;
+
+// BPF_JMP operations
+pub const BPF_JA: u16 = 0x00;
+pub const BPF_JEQ: u16 = 0x10;
+pub const BPF_JGT: u16 = 0x20;
+pub const BPF_JGE: u16 = 0x30;
+pub const BPF_JSET: u16 = 0x40;
+
+// Operand sources of BPF_ALU, BPF_JMP and BPF_RET
+pub const BPF_K: u16 = 0x00;
+pub const BPF_X: u16 = 0x08;
+pub const BPF_A: u16 = 0x10;
+
+// BPF_MISC operations
+pub const BPF_TAX: u16 = 0x00;
+pub const BPF_TXA: u16 = 0x80;
+
+/// The maximum number of instructions of a program.
+pub const BPF_MAXINSNS: usize = 4096;
+/// The number of words of scratch memory.
+pub const BPF_MEMWORDS: usize = 16;
+
+/// An instruction that doesn't jump, like the C macro `BPF_STMT`.
+pub fn bpf_stmt(code: u16, k: u32) -> SockFilter {
+    SockFilter { code: code, jt: 0, jf: 0, k: k }
+}
+
+/// A conditional jump, like the C macro `BPF_JUMP`.  `jt` and `jf` are the
+/// number of instructions to skip if the condition holds or doesn't.
+pub fn bpf_jump(code: u16, k: u32, jt: u8, jf: u8) -> SockFilter {
+    SockFilter { code: code, jt: jt, jf: jf, k: k }
+}
+
+/// Assembles a program, resolving jumps to labels.  Jumps can only go
+/// forward, and a label marks the instruction added after it.
+///
+/// # Example
+///
+/// Keep only IPv4 packets on a packet socket:
+///
+/// ```
+/// use nix::sys::socket::bpf::*;
+///
+/// let filter = FilterBuilder::new()
+///     .ld_abs(BPF_H, 12)
+///     .jump(BPF_JEQ, 0x0800, None, Some("drop"))
+///     .ret(0xffff)
+///     .label("drop")
+///     .ret(0)
+///     .build()
+///     .unwrap();
+///
+/// let ipv4 = [0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x00];
+/// let arp = [0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x06];
+/// assert_eq!(run(&filter, &ipv4).unwrap(), 0xffff);
+/// assert_eq!(run(&filter, &arp).unwrap(), 0);
+/// ```
+pub struct FilterBuilder<'a> {
+    insns: Vec<SockFilter>,
+    labels: Vec<(&'a str, usize)>,
+    // Jump instructions with their true and false targets, or the jump
+    // target of BPF_JA as the true target.  None falls through.
+    jumps: Vec<(usize, Option<&'a str>, Option<&'a str>)>,
+}
+
+impl<'a> FilterBuilder<'a> {
+    pub fn new() -> FilterBuilder<'a> {
+        FilterBuilder {
+            insns: Vec::new(),
+            labels: Vec::new(),
+            jumps: Vec::new(),
+        }
+    }
+
+    /// Add an instruction as is.
+    pub fn insn(&mut self, insn: SockFilter) -> &mut FilterBuilder<'a> {
+        self.insns.push(insn);
+        self
+    }
+
+    /// Make `name` refer to the next instruction.
+    pub fn label(&mut self, name: &'a str) -> &mut FilterBuilder<'a> {
+        self.labels.push((name, self.insns.len()));
+        self
+    }
+
+    /// Load the `size` (`BPF_W`, `BPF_H` or `BPF_B`) bytes at offset `k` of
+    /// the packet into A, in network byte order.
+    pub fn ld_abs(&mut self, size: u16, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LD | size | BPF_ABS, k))
+    }
+
+    /// Load the `size` bytes at offset X + `k` of the packet into A.
+    pub fn ld_ind(&mut self, size: u16, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LD | size | BPF_IND, k))
+    }
+
+    /// Load the length of the packet into A.
+    pub fn ld_len(&mut self) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LD | BPF_W | BPF_LEN, 0))
+    }
+
+    /// Load `k` into A.
+    pub fn ld_imm(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LD | BPF_W | BPF_IMM, k))
+    }
+
+    /// Load scratch memory word `k` into A.
+    pub fn ld_mem(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LD | BPF_W | BPF_MEM, k))
+    }
+
+    /// Load `k` into X.
+    pub fn ldx_imm(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LDX | BPF_W | BPF_IMM, k))
+    }
+
+    /// Load scratch memory word `k` into X.
+    pub fn ldx_mem(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LDX | BPF_W | BPF_MEM, k))
+    }
+
+    /// Load the length of the IPv4 header at offset `k` into X.
+    pub fn ldx_msh(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_LDX | BPF_B | BPF_MSH, k))
+    }
+
+    /// Store A into scratch memory word `k`.
+    pub fn st(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_ST, k))
+    }
+
+    /// Store X into scratch memory word `k`.
+    pub fn stx(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_STX, k))
+    }
+
+    /// Apply the arithmetic operation `op`, like `BPF_ADD`, to A and `k`.
+    pub fn alu(&mut self, op: u16, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_ALU | op | BPF_K, k))
+    }
+
+    /// Apply the arithmetic operation `op` to A and X.
+    pub fn alu_x(&mut self, op: u16) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_ALU | op | BPF_X, 0))
+    }
+
+    /// Copy A to X.
+    pub fn tax(&mut self) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_MISC | BPF_TAX, 0))
+    }
+
+    /// Copy X to A.
+    pub fn txa(&mut self) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_MISC | BPF_TXA, 0))
+    }
+
+    /// Jump to `target` unconditionally.
+    pub fn ja(&mut self, target: &'a str) -> &mut FilterBuilder<'a> {
+        self.jumps.push((self.insns.len(), Some(target), None));
+        self.insn(bpf_stmt(BPF_JMP | BPF_JA, 0))
+    }
+
+    /// Compare A to `k` with `op`, like `BPF_JEQ`, and jump to `jt` if the
+    /// condition holds and `jf` if it doesn't.  `None` continues with the
+    /// next instruction.
+    pub fn jump(&mut self, op: u16, k: u32, jt: Option<&'a str>, jf: Option<&'a str>) -> &mut FilterBuilder<'a> {
+        self.jumps.push((self.insns.len(), jt, jf));
+        self.insn(bpf_jump(BPF_JMP | op | BPF_K, k, 0, 0))
+    }
+
+    /// Compare A to X with `op` and jump like `jump`.
+    pub fn jump_x(&mut self, op: u16, jt: Option<&'a str>, jf: Option<&'a str>) -> &mut FilterBuilder<'a> {
+        self.jumps.push((self.insns.len(), jt, jf));
+        self.insn(bpf_jump(BPF_JMP | op | BPF_X, 0, 0, 0))
+    }
+
+    /// Return `k`, the number of bytes of the packet to keep.
+    pub fn ret(&mut self, k: u32) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_RET | BPF_K, k))
+    }
+
+    /// Return A.
+    pub fn ret_a(&mut self) -> &mut FilterBuilder<'a> {
+        self.insn(bpf_stmt(BPF_RET | BPF_A, 0))
+    }
+
+    fn offset(&self, from: usize, target: Option<&'a str>) -> Result<usize> {
+        let name = match target {
+            Some(name) => name,
+            None => return Ok(0),
+        };
+        let mut found = self.labels.iter().filter(|&&(n, _)| n == name);
+        match (found.next(), found.next()) {
+            (Some(&(_, to)), None) if to > from && to < self.insns.len() => Ok(to - from - 1),
+            _ => Err(Error::Sys(Errno::EINVAL)),
+        }
+    }
+
+    /// Resolve the jumps and return the program.  Fails with `EINVAL` if a
+    /// label is unknown, defined twice, backwards or too far away for a
+    /// conditional jump, or if the program is empty or too long.
+    pub fn build(&self) -> Result<Vec<SockFilter>> {
+        if self.insns.is_empty() || self.insns.len() > BPF_MAXINSNS {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        let mut insns = self.insns.clone();
+        for &(at, jt, jf) in &self.jumps {
+            let jt = try!(self.offset(at, jt));
+            let jf = try!(self.offset(at, jf));
+            if insns[at].code & 0xf0 == BPF_JA {
+                insns[at].k = jt as u32;
+            } else if jt > u8::max_value() as usize || jf > u8::max_value() as usize {
+                return Err(Error::Sys(Errno::EINVAL));
+            } else {
+                insns[at].jt = jt as u8;
+                insns[at].jf = jf as u8;
+            }
+        }
+        Ok(insns)
+    }
+}
+
+fn load(packet: &[u8], offset: u32, size: u16) -> Option<u32> {
+    let len = match size {
+        BPF_W => 4,
+        BPF_H => 2,
+        BPF_B => 1,
+        _ => return None,
+    };
+    let start = offset as usize;
+    let end = match start.checked_add(len) {
+        Some(end) if end <= packet.len() => end,
+        _ => return None,
+    };
+    Some(packet[start..end].iter().fold(0, |val, &b| val << 8 | b as u32))
+}
+
+fn mem_index(k: u32) -> Result<usize> {
+    if (k as usize) < BPF_MEMWORDS {
+        Ok(k as usize)
+    } else {
+        Err(Error::Sys(Errno::EINVAL))
+    }
+}
+
+/// Run `filter` on `packet` like the kernel would, and return the number of
+/// bytes of it to keep.
+///
+/// Loads outside the packet and division by zero return 0, as in the
+/// kernel.  Invalid instructions, jumps past the end of the program and the
+/// ancillary data loads at negative offsets fail with `EINVAL`.
+pub fn run(filter: &[SockFilter], packet: &[u8]) -> Result<u32> {
+    let mut a: u32 = 0;
+    let mut x: u32 = 0;
+    let mut mem = [0u32; BPF_MEMWORDS];
+    let mut pc = 0;
+
+    loop {
+        let insn = match filter.get(pc) {
+            Some(insn) => *insn,
+            None => return Err(Error::Sys(Errno::EINVAL)),
+        };
+        pc += 1;
+        let k = insn.k;
+        let src = if insn.code & BPF_X != 0 { x } else { k };
+
+        match insn.code & 0x07 {
+            class @ BPF_LD | class @ BPF_LDX => {
+                let mode = insn.code & 0xe0;
+                let val = match mode {
+                    BPF_IMM => k,
+                    BPF_LEN => packet.len() as u32,
+                    BPF_MEM => mem[try!(mem_index(k))],
+                    BPF_ABS | BPF_IND if class == BPF_LD => {
+                        let offset = if mode == BPF_IND { x.wrapping_add(k) } else { k };
+                        if (offset as i32) < 0 {
+                            return Err(Error::Sys(Errno::EINVAL));
+                        }
+                        match load(packet, offset, insn.code & 0x18) {
+                            Some(val) => val,
+                            None => return Ok(0),
+                        }
+                    }
+                    BPF_MSH if class == BPF_LDX => {
+                        match packet.get(k as usize) {
+                            Some(&b) => 4 * (b & 0xf) as u32,
+                            None => return Ok(0),
+                        }
+                    }
+                    _ => return Err(Error::Sys(Errno::EINVAL)),
+                };
+                if class == BPF_LD {
+                    a = val;
+                } else {
+                    x = val;
+                }
+            }
+            BPF_ST => mem[try!(mem_index(k))] = a,
+            BPF_STX => mem[try!(mem_index(k))] = x,
+            BPF_ALU => {
+                a = match insn.code & 0xf0 {
+                    BPF_ADD => a.wrapping_add(src),
+                    BPF_SUB => a.wrapping_sub(src),
+                    BPF_MUL => a.wrapping_mul(src),
+                    BPF_DIV if src == 0 => return Ok(0),
+                    BPF_DIV => a / src,
+                    BPF_MOD if src == 0 => return Ok(0),
+                    BPF_MOD => a % src,
+                    BPF_OR => a | src,
+                    BPF_AND => a & src,
+                    BPF_LSH => a.checked_shl(src).unwrap_or(0),
+                    BPF_RSH => a.checked_shr(src).unwrap_or(0),
+                    BPF_NEG => a.wrapping_neg(),
+                    BPF_XOR => a ^ src,
+                    _ => return Err(Error::Sys(Errno::EINVAL)),
+                };
+            }
+            BPF_JMP => {
+                let cond = match insn.code & 0xf0 {
+                    BPF_JA => {
+                        pc += k as usize;
+                        continue;
+                    }
+                    BPF_JEQ => a == src,
+                    BPF_JGT => a > src,
+                    BPF_JGE => a >= src,
+                    BPF_JSET => a & src != 0,
+                    _ => return Err(Error::Sys(Errno::EINVAL)),
+                };
+                let skip = if cond { insn.jt } else { insn.jf };
+                pc += skip as usize;
+            }
+            BPF_RET => {
+                return match insn.code & 0x18 {
+                    BPF_K => Ok(k),
+                    BPF_X => Ok(x),
+                    BPF_A => Ok(a),
+                    _ => Err(Error::Sys(Errno::EINVAL)),
+                };
+            }
+            BPF_MISC => {
+                match insn.code & 0xf8 {
+                    BPF_TAX => x = a,
+                    BPF_TXA => a = x,
+                    _ => return Err(Error::Sys(Errno::EINVAL)),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Attach `filter` to the socket `fd`, replacing any filter attached
+/// before.  The kernel copies the program.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/socket.7.html)
+pub fn attach_filter(fd: RawFd, filter: &[SockFilter]) -> Result<()> {
+    if filter.len() > c_ushort::max_value() as usize {
+        return Err(Error::Sys(Errno::EINVAL));
+    }
+    let prog = sock_fprog {
+        len: filter.len() as c_ushort,
+        filter: filter.as_ptr() as *mut SockFilter,
+    };
+    setsockopt(fd, sockopt::AttachFilter, &prog)
+}
+
+/// Remove the filter attached to the socket `fd`.
+pub fn detach_filter(fd: RawFd) -> Result<()> {
+    setsockopt(fd, sockopt::DetachFilter, &0)
+}
+
+/// Prevent the filter of the socket `fd` from being replaced or detached,
+/// for example before handing it to less privileged code.
+pub fn lock_filter(fd: RawFd) -> Result<()> {
+    setsockopt(fd, sockopt::LockFilter, &true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An Ethernet frame carrying an IPv4 UDP packet to port 53
+    static UDP_DNS: [u8; 42] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x08, 0x00,
+        0x45, 0x00, 0x00, 0x1c, 0x00, 0x00, 0x00, 0x00, 0x40, 0x11, 0x00, 0x00,
+        0x0a, 0x00, 0x00, 0x01, 0x0a, 0x00, 0x00, 0x02,
+        0x30, 0x39, 0x00, 0x35, 0x00, 0x08, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn udp_port_filter() {
+        let filter = FilterBuilder::new()
+            .ld_abs(BPF_H, 12)
+            .jump(BPF_JEQ, 0x0800, None, Some("drop"))
+            .ld_abs(BPF_B, 23)
+            .jump(BPF_JEQ, 17, None, Some("drop"))
+            .ldx_msh(14)
+            .ld_ind(BPF_H, 16)
+            .jump(BPF_JEQ, 53, Some("keep"), Some("drop"))
+            .label("keep")
+            .ret_a()
+            .label("drop")
+            .ret(0)
+            .build()
+            .unwrap();
+
+        assert_eq!(filter[1], bpf_jump(BPF_JMP | BPF_JEQ | BPF_K, 0x0800, 0, 6));
+        assert_eq!(run(&filter, &UDP_DNS).unwrap(), 53);
+
+        let mut other_port = UDP_DNS;
+        other_port[37] = 54;
+        assert_eq!(run(&filter, &other_port).unwrap(), 0);
+        // Too short to hold the port
+        assert_eq!(run(&filter, &UDP_DNS[..30]).unwrap(), 0);
+    }
+
+    #[test]
+    fn alu_and_memory() {
+        let filter = FilterBuilder::new()
+            .ld_len()
+            .alu(BPF_MUL, 3)
+            .st(2)
+            .ldx_imm(2)
+            .ld_mem(2)
+            .alu_x(BPF_DIV)
+            .alu(BPF_SUB, 1)
+            .tax()
+            .ld_imm(0)
+            .txa()
+            .ret_a()
+            .build()
+            .unwrap();
+        assert_eq!(run(&filter, &[0; 10]).unwrap(), 14);
+
+        let div0 = [bpf_stmt(BPF_LDX | BPF_IMM, 0), bpf_stmt(BPF_ALU | BPF_DIV | BPF_X, 0),
+                    bpf_stmt(BPF_RET | BPF_K, 1)];
+        assert_eq!(run(&div0, &[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn invalid_programs() {
+        assert!(FilterBuilder::new().build().is_err());
+        assert!(FilterBuilder::new().ja("nowhere").ret(0).build().is_err());
+        assert!(FilterBuilder::new().label("back").ret(0).ja("back").build().is_err());
+
+        let mut far = FilterBuilder::new();
+        far.jump(BPF_JEQ, 0, Some("end"), None);
+        for _ in 0..300 {
+            far.ret(0);
+        }
+        far.label("end").ret(1);
+        assert!(far.build().is_err());
+
+        // Falls off the end
+        assert!(run(&[bpf_stmt(BPF_LD | BPF_IMM, 0)], &[]).is_err());
+        // Scratch memory out of range
+        assert!(run(&[bpf_stmt(BPF_ST, 16), bpf_stmt(BPF_RET | BPF_K, 0)], &[]).is_err());
+    }
+}
@@ -2,7 +2,7 @@ pub use self::os::*;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod os {
-    use libc::{self, c_int, uint8_t};
+    use libc::{self, c_int, c_ushort, uint8_t};
 
     pub const AF_UNIX: c_int  = 1;
     pub const AF_LOCAL: c_int = AF_UNIX;
@@ -23,6 +23,7 @@ mod os {
     pub const SOL_UDP: c_int    = 17;
     pub const SOL_IPV6: c_int   = 41;
     pub const SOL_NETLINK: c_int = 270;
+    pub const SOL_PACKET: c_int = 263;
     pub const IPPROTO_IP: c_int = SOL_IP;
     pub const IPPROTO_IPV6: c_int = SOL_IPV6;
     pub const IPPROTO_TCP: c_int = SOL_TCP;
@@ -60,8 +61,12 @@ mod os {
     #[cfg(not(target_arch="arm"))]
     pub const SO_SNDBUFFORCE: c_int = libc::SO_SNDBUFFORCE;
     pub const SO_TIMESTAMP: c_int = 29;
+    pub const SO_TIMESTAMPNS: c_int = 35;
     pub const SO_TYPE: c_int = libc::SO_TYPE;
     pub const SO_BUSY_POLL: c_int = 46;
+    pub const SO_ATTACH_FILTER: c_int = 26;
+    pub const SO_DETACH_FILTER: c_int = 27;
+    pub const SO_LOCK_FILTER: c_int = 44;
     #[cfg(target_os = "linux")]
     pub const SO_ORIGINAL_DST: c_int = 80;
 
@@ -70,8 +75,22 @@ mod os {
     pub const TCP_MAXSEG: c_int = 2;
     pub const TCP_CORK: c_int = 3;
     pub const TCP_KEEPIDLE: c_int = libc::TCP_KEEPIDLE;
+    pub const TCP_KEEPINTVL: c_int = 5;
+    pub const TCP_KEEPCNT: c_int = 6;
+    pub const TCP_INFO: c_int = 11;
+    pub const TCP_QUICKACK: c_int = 12;
+    pub const TCP_CONGESTION: c_int = 13;
+    pub const TCP_USER_TIMEOUT: c_int = 18;
+    pub const TCP_FASTOPEN: c_int = 23;
 
     // Socket options for the IP layer of the socket
+    pub const IP_TOS: c_int = 1;
+    pub const IP_TTL: c_int = 2;
+    pub const IP_PKTINFO: c_int = 8;
+    pub const IP_RECVERR: c_int = 11;
+    pub const IP_RECVTTL: c_int = 12;
+    pub const IP_FREEBIND: c_int = 15;
+    pub const IP_TRANSPARENT: c_int = 19;
     pub const IP_MULTICAST_IF: c_int = 32;
 
     pub type IpMulticastTtl = uint8_t;
@@ -83,6 +102,53 @@ mod os {
 
     pub const IPV6_ADD_MEMBERSHIP: c_int = libc::IPV6_ADD_MEMBERSHIP;
     pub const IPV6_DROP_MEMBERSHIP: c_int = libc::IPV6_DROP_MEMBERSHIP;
+    pub const IPV6_V6ONLY: c_int = 26;
+    pub const IPV6_RECVPKTINFO: c_int = 49;
+    pub const IPV6_PKTINFO: c_int = 50;
+    pub const IPV6_TCLASS: c_int = 67;
+
+    // Socket options for netlink sockets
+    pub const NETLINK_ADD_MEMBERSHIP: c_int = 1;
+    pub const NETLINK_DROP_MEMBERSHIP: c_int = 2;
+
+    // Socket options for packet sockets
+    pub const PACKET_ADD_MEMBERSHIP: c_int = 1;
+    pub const PACKET_DROP_MEMBERSHIP: c_int = 2;
+    pub const PACKET_FANOUT: c_int = 18;
+
+    // Membership types of packet_mreq
+    pub const PACKET_MR_MULTICAST: c_ushort = 0;
+    pub const PACKET_MR_PROMISC: c_ushort = 1;
+    pub const PACKET_MR_ALLMULTI: c_ushort = 2;
+
+    // Fanout modes and flags, combined with the group id as
+    // `id | (mode << 16) | flags` for PACKET_FANOUT
+    pub const PACKET_FANOUT_HASH: u32 = 0;
+    pub const PACKET_FANOUT_LB: u32 = 1;
+    pub const PACKET_FANOUT_CPU: u32 = 2;
+    pub const PACKET_FANOUT_ROLLOVER: u32 = 3;
+    pub const PACKET_FANOUT_RND: u32 = 4;
+    pub const PACKET_FANOUT_QM: u32 = 5;
+    pub const PACKET_FANOUT_FLAG_ROLLOVER: u32 = 0x1000;
+    pub const PACKET_FANOUT_FLAG_UNIQUEID: u32 = 0x2000;
+    pub const PACKET_FANOUT_FLAG_DEFRAG: u32 = 0x8000;
+
+    // Packet types of sockaddr_ll
+    pub const PACKET_HOST: u8 = 0;
+    pub const PACKET_BROADCAST: u8 = 1;
+    pub const PACKET_MULTICAST: u8 = 2;
+    pub const PACKET_OTHERHOST: u8 = 3;
+    pub const PACKET_OUTGOING: u8 = 4;
+
+    // Ethernet protocols for packet sockets, in host byte order
+    pub const ETH_P_ALL: u16 = 0x0003;
+    pub const ETH_P_IP: u16 = 0x0800;
+    pub const ETH_P_ARP: u16 = 0x0806;
+    pub const ETH_P_IPV6: u16 = 0x86dd;
+
+    // ARP hardware types of sockaddr_ll
+    pub const ARPHRD_ETHER: u16 = 1;
+    pub const ARPHRD_LOOPBACK: u16 = 772;
 
     pub type InAddrT = u32;
 
@@ -112,6 +178,9 @@ mod os {
 
     // Ancillary message types
     pub const SCM_RIGHTS: c_int = 1;
+    pub const SCM_CREDENTIALS: c_int = 2;
+    pub const SCM_TIMESTAMP: c_int = SO_TIMESTAMP;
+    pub const SCM_TIMESTAMPNS: c_int = SO_TIMESTAMPNS;
 }
 
 // Not all of these constants exist on freebsd
@@ -214,6 +283,7 @@ mod os {
     pub const TCP_KEEPIDLE: c_int = 3;
 
     // Socket options for the IP layer of the socket
+    pub const IP_TOS: c_int = 3;
     pub const IP_MULTICAST_IF: c_int = 9;
 
     pub type IpMulticastTtl = uint8_t;
@@ -225,6 +295,7 @@ mod os {
 
     pub const IPV6_JOIN_GROUP: c_int = libc::IPV6_JOIN_GROUP;
     pub const IPV6_LEAVE_GROUP: c_int = libc::IPV6_LEAVE_GROUP;
+    pub const IPV6_V6ONLY: c_int = 27;
 
     pub type InAddrT = u32;
 
@@ -252,6 +323,10 @@ mod os {
 
     // Ancillary message types
     pub const SCM_RIGHTS: c_int = 1;
+    #[cfg(not(target_os = "netbsd"))]
+    pub const SCM_TIMESTAMP: c_int = 2;
+    #[cfg(target_os = "netbsd")]
+    pub const SCM_TIMESTAMP: c_int = 8;
 }
 
 #[cfg(target_os = "dragonfly")]
@@ -301,6 +376,7 @@ mod os {
     pub const TCP_KEEPIDLE: c_int = 0x100;
 
     // Socket options for the IP layer of the socket
+    pub const IP_TOS: c_int = 3;
     pub const IP_MULTICAST_IF: c_int = 9;
 
     pub type IpMulticastTtl = uint8_t;
@@ -311,6 +387,7 @@ mod os {
     pub const IP_DROP_MEMBERSHIP: c_int = 13;
     pub const IPV6_JOIN_GROUP: c_int = libc::IPV6_JOIN_GROUP;
     pub const IPV6_LEAVE_GROUP: c_int = libc::IPV6_LEAVE_GROUP;
+    pub const IPV6_V6ONLY: c_int = 27;
 
     pub type InAddrT = u32;
 
@@ -432,6 +509,17 @@ mod test {
             // SO_DOMAIN,
             // SO_MARK,
             TCP_CORK,
+            TCP_KEEPINTVL,
+            TCP_KEEPCNT,
+            TCP_INFO,
+            TCP_QUICKACK,
+            TCP_CONGESTION,
+            IP_TOS,
+            IP_FREEBIND,
+            IP_TRANSPARENT,
+            IPV6_V6ONLY,
+            SO_ATTACH_FILTER,
+            SO_DETACH_FILTER,
             // SO_BUSY_POLL,
             // SO_RXQ_OVFL,
             SO_PRIORITY,
@@ -5,8 +5,10 @@ pub use libc::{socket, listen, bind, accept, connect, setsockopt, sendto, recvfr
 
 use libc::{c_int, c_void, socklen_t, size_t, ssize_t};
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "android"))]
 use libc::c_uint;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc::timespec;
 
 use sys::uio::IoVec;
 
@@ -50,6 +52,15 @@ pub struct cmsghdr {
     pub cmsg_data: [type_of_cmsg_data; 0]
 }
 
+// A msghdr together with the number of bytes transferred for it, used by
+// sendmmsg and recvmmsg.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct mmsghdr {
+    pub msg_hdr: msghdr<'static>,
+    pub msg_len: c_uint,
+}
+
 extern {
     pub fn getsockopt(
         sockfd: c_int,
@@ -67,4 +78,19 @@ extern {
 
     pub fn sendmsg(sockfd: c_int, msg: *const msghdr, flags: c_int) -> ssize_t;
     pub fn recvmsg(sockfd: c_int, msg: *mut msghdr, flags: c_int) -> ssize_t;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn sendmmsg(
+        sockfd: c_int,
+        msgvec: *mut mmsghdr,
+        vlen: c_uint,
+        flags: c_int) -> c_int;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recvmmsg(
+        sockfd: c_int,
+        msgvec: *mut mmsghdr,
+        vlen: c_uint,
+        flags: c_int,
+        timeout: *mut timespec) -> c_int;
 }
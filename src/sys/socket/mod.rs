@@ -6,14 +6,23 @@ use features;
 use fcntl::{fcntl, FD_CLOEXEC, O_NONBLOCK};
 use fcntl::FcntlArg::{F_SETFD, F_SETFL};
 use libc::{c_void, c_int, socklen_t, size_t, pid_t, uid_t, gid_t};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc::{c_uint, c_ushort, timespec};
 use std::{mem, ptr, slice};
 use std::os::unix::io::RawFd;
+use sys::time::TimeVal;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use sys::time::TimeSpec;
 use sys::uio::IoVec;
 
 mod addr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod bpf;
 mod consts;
 mod ffi;
 mod multicast;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod netlink;
 pub mod sockopt;
 
 /*
@@ -25,6 +34,7 @@ pub mod sockopt;
 pub use self::addr::{
     AddressFamily,
     SockAddr,
+    SockaddrStorage,
     InetAddr,
     UnixAddr,
     IpAddr,
@@ -33,6 +43,8 @@ pub use self::addr::{
 };
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub use ::sys::socket::addr::netlink::NetlinkAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use ::sys::socket::addr::link::{LinkAddr, sockaddr_ll};
 
 pub use libc::{
     in_addr,
@@ -48,6 +60,8 @@ pub use self::multicast::{
     ip_mreq,
     ipv6_mreq,
 };
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::multicast::packet_mreq;
 pub use self::consts::*;
 
 pub use libc::sockaddr_storage;
@@ -113,7 +127,8 @@ pub struct RecvMsg<'a> {
     // The number of bytes received.
     pub bytes: usize,
     cmsg_buffer: &'a [u8],
-    pub address: Option<SockAddr>,
+    // The source address, if the socket reported one.
+    pub address: Option<SockaddrStorage>,
     pub flags: MsgFlags,
 }
 
@@ -156,18 +171,63 @@ impl<'a> Iterator for CmsgIterator<'a> {
         }
         self.0 = &buf[cmsg_align(cmsg_len)..];
 
+        let data = &cmsg.cmsg_data as *const _ as *const u8;
+
         match (cmsg.cmsg_level, cmsg.cmsg_type) {
             (SOL_SOCKET, SCM_RIGHTS) => unsafe {
                 Some(ControlMessage::ScmRights(
                     slice::from_raw_parts(
-                        &cmsg.cmsg_data as *const _ as *const _, 1)))
+                        data as *const _,
+                        len / mem::size_of::<RawFd>())))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (SOL_SOCKET, SCM_CREDENTIALS) if len >= mem::size_of::<ucred>() => unsafe {
+                Some(ControlMessage::ScmCredentials(
+                    ptr::read(data as *const ucred)))
+            },
+            (SOL_SOCKET, SCM_TIMESTAMP) if len >= mem::size_of::<TimeVal>() => unsafe {
+                Some(ControlMessage::ScmTimestamp(
+                    ptr::read(data as *const TimeVal)))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (SOL_SOCKET, SCM_TIMESTAMPNS) if len >= mem::size_of::<TimeSpec>() => unsafe {
+                Some(ControlMessage::ScmTimestampNs(
+                    ptr::read(data as *const TimeSpec)))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (IPPROTO_IP, IP_PKTINFO) if len >= mem::size_of::<in_pktinfo>() => unsafe {
+                Some(ControlMessage::Ipv4PacketInfo(
+                    ptr::read(data as *const in_pktinfo)))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (IPPROTO_IPV6, IPV6_PKTINFO) if len >= mem::size_of::<in6_pktinfo>() => unsafe {
+                Some(ControlMessage::Ipv6PacketInfo(
+                    ptr::read(data as *const in6_pktinfo)))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (IPPROTO_IP, IP_TTL) if len >= mem::size_of::<c_int>() => unsafe {
+                Some(ControlMessage::IpTtl(ptr::read(data as *const c_int)))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            (IPPROTO_IP, IP_RECVERR) if len >= mem::size_of::<sock_extended_err>() => unsafe {
+                let ee = ptr::read(data as *const sock_extended_err);
+                // The address of the node that generated the error, if
+                // any, follows the error structure (SO_EE_OFFENDER).
+                let offender = data.offset(mem::size_of::<sock_extended_err>() as isize)
+                    as *const sockaddr_in;
+                let offender = if len >= mem::size_of::<sock_extended_err>() +
+                                          mem::size_of::<sockaddr_in>() &&
+                                  (*offender).sin_family as c_int == AF_INET {
+                    Some(ptr::read(offender))
+                } else {
+                    None
+                };
+                Some(ControlMessage::Ipv4RecvErr(ee, offender))
             },
             (_, _) => unsafe {
                 Some(ControlMessage::Unknown(UnknownCmsg(
                     &cmsg,
-                    slice::from_raw_parts(
-                        &cmsg.cmsg_data as *const _ as *const _,
-                        len))))
+                    slice::from_raw_parts(data, len))))
             }
         }
     }
@@ -182,6 +242,49 @@ pub enum ControlMessage<'a> {
     /// "Ancillary messages" section of the
     /// [unix(7) man page](http://man7.org/linux/man-pages/man7/unix.7.html).
     ScmRights(&'a [RawFd]),
+    /// A message of type SCM_CREDENTIALS, containing the pid, uid and gid
+    /// of a process connected to a Unix socket.  Received when
+    /// `sockopt::PassCred` is enabled.  See the "Ancillary messages"
+    /// section of the
+    /// [unix(7) man page](http://man7.org/linux/man-pages/man7/unix.7.html).
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ScmCredentials(ucred),
+    /// A message of type SCM_TIMESTAMP, containing the time the packet was
+    /// received by the kernel.  Received when `sockopt::ReceiveTimestamp`
+    /// is enabled.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/socket.7.html)
+    ScmTimestamp(TimeVal),
+    /// A message of type SCM_TIMESTAMPNS, the nanosecond resolution
+    /// variant of `ScmTimestamp`.  Received when
+    /// `sockopt::ReceiveTimestampNs` is enabled.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/socket.7.html)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ScmTimestampNs(TimeSpec),
+    /// A message of type IP_PKTINFO, containing the interface a packet
+    /// arrived on and its destination address.  Received when
+    /// `sockopt::Ipv4PacketInfo` is enabled; when sent, selects the
+    /// outgoing interface and source address.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Ipv4PacketInfo(in_pktinfo),
+    /// A message of type IPV6_PKTINFO, the IPv6 counterpart of
+    /// `Ipv4PacketInfo`.  Received when `sockopt::Ipv6RecvPacketInfo` is
+    /// enabled.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ipv6.7.html)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Ipv6PacketInfo(in6_pktinfo),
+    /// A message of type IP_TTL, containing the time to live of a received
+    /// packet when `sockopt::Ipv4RecvTtl` is enabled, or the time to live
+    /// to use for an outgoing one.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    IpTtl(c_int),
+    /// A message of type IP_RECVERR, read from the error queue with
+    /// `MSG_ERRQUEUE` when `sockopt::Ipv4RecvErr` is enabled.  Contains the
+    /// extended error and the address of the node that caused it, if known.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Ipv4RecvErr(sock_extended_err, Option<sockaddr_in>),
     #[doc(hidden)]
     Unknown(UnknownCmsg<'a>),
 }
@@ -207,36 +310,108 @@ impl<'a> ControlMessage<'a> {
             ControlMessage::ScmRights(fds) => {
                 mem::size_of_val(fds)
             },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::ScmCredentials(ref cred) => {
+                mem::size_of_val(cred)
+            },
+            ControlMessage::ScmTimestamp(ref t) => {
+                mem::size_of_val(t)
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::ScmTimestampNs(ref t) => {
+                mem::size_of_val(t)
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv4PacketInfo(ref info) => {
+                mem::size_of_val(info)
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv6PacketInfo(ref info) => {
+                mem::size_of_val(info)
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::IpTtl(ref ttl) => {
+                mem::size_of_val(ttl)
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv4RecvErr(ref ee, ref offender) => {
+                mem::size_of_val(ee) + match *offender {
+                    Some(ref addr) => mem::size_of_val(addr),
+                    None => 0,
+                }
+            },
             ControlMessage::Unknown(UnknownCmsg(_, bytes)) => {
                 mem::size_of_val(bytes)
             }
         }
     }
 
+    // Write the cmsghdr for this message and the padding following it,
+    // leaving buf pointing at the start of the data.
+    unsafe fn encode_header<'b>(&self, buf: &mut &'b mut [u8], level: c_int, ty: c_int) {
+        let cmsg = cmsghdr {
+            cmsg_len: self.len() as type_of_cmsg_len,
+            cmsg_level: level,
+            cmsg_type: ty,
+            cmsg_data: [],
+        };
+        copy_bytes(&cmsg, buf);
+
+        let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
+            mem::size_of_val(&cmsg);
+
+        let mut tmpbuf = &mut [][..];
+        mem::swap(&mut tmpbuf, buf);
+        let (_padding, mut remainder) = tmpbuf.split_at_mut(padlen);
+        mem::swap(buf, &mut remainder);
+    }
+
     // Unsafe: start and end of buffer must be size_t-aligned (that is,
     // cmsg_align'd). Updates the provided slice; panics if the buffer
     // is too small.
     unsafe fn encode_into<'b>(&self, buf: &mut &'b mut [u8]) {
         match *self {
             ControlMessage::ScmRights(fds) => {
-                let cmsg = cmsghdr {
-                    cmsg_len: self.len() as type_of_cmsg_len,
-                    cmsg_level: SOL_SOCKET,
-                    cmsg_type: SCM_RIGHTS,
-                    cmsg_data: [],
-                };
-                copy_bytes(&cmsg, buf);
-
-                let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
-                    mem::size_of_val(&cmsg);
-
-                let mut tmpbuf = &mut [][..];
-                mem::swap(&mut tmpbuf, buf);
-                let (_padding, mut remainder) = tmpbuf.split_at_mut(padlen);
-                mem::swap(buf, &mut remainder);
-
+                self.encode_header(buf, SOL_SOCKET, SCM_RIGHTS);
                 copy_bytes(fds, buf);
             },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::ScmCredentials(ref cred) => {
+                self.encode_header(buf, SOL_SOCKET, SCM_CREDENTIALS);
+                copy_bytes(cred, buf);
+            },
+            ControlMessage::ScmTimestamp(ref t) => {
+                self.encode_header(buf, SOL_SOCKET, SCM_TIMESTAMP);
+                copy_bytes(t, buf);
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::ScmTimestampNs(ref t) => {
+                self.encode_header(buf, SOL_SOCKET, SCM_TIMESTAMPNS);
+                copy_bytes(t, buf);
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv4PacketInfo(ref info) => {
+                self.encode_header(buf, IPPROTO_IP, IP_PKTINFO);
+                copy_bytes(info, buf);
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv6PacketInfo(ref info) => {
+                self.encode_header(buf, IPPROTO_IPV6, IPV6_PKTINFO);
+                copy_bytes(info, buf);
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::IpTtl(ref ttl) => {
+                self.encode_header(buf, IPPROTO_IP, IP_TTL);
+                copy_bytes(ttl, buf);
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ControlMessage::Ipv4RecvErr(ref ee, ref offender) => {
+                self.encode_header(buf, IPPROTO_IP, IP_RECVERR);
+                copy_bytes(ee, buf);
+                if let Some(ref addr) = *offender {
+                    copy_bytes(addr, buf);
+                }
+            },
             ControlMessage::Unknown(UnknownCmsg(orig_cmsg, bytes)) => {
                 copy_bytes(orig_cmsg, buf);
                 copy_bytes(bytes, buf);
@@ -297,7 +472,7 @@ pub fn sendmsg<'a>(fd: RawFd, iov: &[IoVec<&'a [u8]>], cmsgs: &[ControlMessage<'
 /// optionally receive ancillary data into the provided buffer.
 /// If no ancillary data is desired, use () as the type parameter.
 pub fn recvmsg<'a, T>(fd: RawFd, iov: &[IoVec<&mut [u8]>], cmsg_buffer: Option<&'a mut CmsgSpace<T>>, flags: MsgFlags) -> Result<RecvMsg<'a>> {
-    let mut address: sockaddr_storage = unsafe { mem::uninitialized() };
+    let mut address: sockaddr_storage = unsafe { mem::zeroed() };
     let (msg_control, msg_controllen) = match cmsg_buffer {
         Some(cmsg_buffer) => (cmsg_buffer as *mut _, mem::size_of_val(cmsg_buffer)),
         None => (0 as *mut _, 0),
@@ -317,13 +492,246 @@ pub fn recvmsg<'a, T>(fd: RawFd, iov: &[IoVec<&mut [u8]>], cmsg_buffer: Option<&
         bytes: try!(Errno::result(ret)) as usize,
         cmsg_buffer: slice::from_raw_parts(mhdr.msg_control as *const u8,
                                            mhdr.msg_controllen as usize),
-        address: sockaddr_storage_to_addr(&address,
-                                          mhdr.msg_namelen as usize).ok(),
+        address: storage_to_option(address, mhdr.msg_namelen),
         flags: MsgFlags::from_bits_truncate(mhdr.msg_flags),
     } })
 }
 
 
+/// Preallocated headers, addresses and control message buffers for
+/// `sendmmsg` and `recvmmsg`.  Create one for the largest batch you intend
+/// to transfer and reuse it across calls; neither function allocates.
+///
+/// The type parameter sizes the per-message control message buffer in the
+/// same way as `CmsgSpace`.  If no ancillary data is desired, use ().
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct MultiHeaders<T> {
+    items: Vec<ffi::mmsghdr>,
+    addresses: Vec<sockaddr_storage>,
+    cmsg_buffers: Vec<CmsgSpace<T>>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T> MultiHeaders<T> {
+    /// Preallocate room for `num_slices` messages.
+    pub fn new(num_slices: usize) -> Self {
+        MultiHeaders {
+            items: (0..num_slices).map(|_| unsafe { mem::zeroed() }).collect(),
+            addresses: (0..num_slices).map(|_| unsafe { mem::zeroed() }).collect(),
+            cmsg_buffers: (0..num_slices).map(|_| CmsgSpace::new()).collect(),
+        }
+    }
+
+    /// The maximum number of messages transferred per call.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether no messages can be transferred, i.e. `len` is zero.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Size of the control message buffer of each message; zero if the
+    // caller asked for none.
+    fn cmsg_len(&self) -> usize {
+        if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            mem::size_of::<CmsgSpace<T>>()
+        }
+    }
+}
+
+/// An iterator over the messages received by `recvmmsg`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct MultiResults<'a, T: 'a> {
+    data: &'a MultiHeaders<T>,
+    current: usize,
+    received: usize,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, T> Iterator for MultiResults<'a, T> {
+    type Item = RecvMsg<'a>;
+
+    fn next(&mut self) -> Option<RecvMsg<'a>> {
+        if self.current >= self.received {
+            return None;
+        }
+        let data = self.data;
+        let hdr = &data.items[self.current];
+        let address = &data.addresses[self.current];
+        self.current += 1;
+
+        let cmsg_buffer = if hdr.msg_hdr.msg_control.is_null() {
+            &[][..]
+        } else {
+            unsafe {
+                slice::from_raw_parts(hdr.msg_hdr.msg_control as *const u8,
+                                      hdr.msg_hdr.msg_controllen as usize)
+            }
+        };
+        Some(RecvMsg {
+            bytes: hdr.msg_len as usize,
+            cmsg_buffer: cmsg_buffer,
+            address: storage_to_option(*address, hdr.msg_hdr.msg_namelen),
+            flags: MsgFlags::from_bits_truncate(hdr.msg_hdr.msg_flags),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.received - self.current;
+        (remaining, Some(remaining))
+    }
+}
+
+/// An iterator over the number of bytes sent for each message by
+/// `sendmmsg`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct MultiSendResults<'a> {
+    items: slice::Iter<'a, ffi::mmsghdr>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Iterator for MultiSendResults<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.items.next().map(|hdr| hdr.msg_len as usize)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+/// Send multiple messages on a socket with a single system call.
+///
+/// `slices` holds the data of each message.  The message at index `i` is
+/// directed at `addrs[i]` and accompanied by `cmsgs[i]`, if those slices
+/// are long enough and the entry is present.  At most `data.len()` messages
+/// are sent.  Returns the number of bytes sent for each message that was
+/// sent, which may be fewer than requested.
+///
+/// Fails with `EINVAL` if the control messages of a message do not fit in
+/// the buffers of `data`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/sendmmsg.2.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn sendmmsg<'a, 'b, I, C, T>(fd: RawFd, data: &'a mut MultiHeaders<T>,
+                                 slices: &[I], addrs: &[Option<SockAddr>],
+                                 cmsgs: &[C], flags: MsgFlags)
+                                 -> Result<MultiSendResults<'a>>
+    where I: AsRef<[IoVec<&'b [u8]>]>, C: AsRef<[ControlMessage<'b>]>
+{
+    let count = ::std::cmp::min(slices.len(), data.items.len());
+    let cmsg_capacity = mem::size_of::<CmsgSpace<T>>();
+
+    for (i, slice) in slices.iter().take(count).enumerate() {
+        let iov = slice.as_ref();
+        let (name, namelen) = match addrs.get(i) {
+            Some(&Some(ref addr)) => {
+                let (x, y) = unsafe { addr.as_ffi_pair() };
+                (x as *const _ as *const c_void, y)
+            },
+            _ => (ptr::null(), 0),
+        };
+
+        let msg_cmsgs = cmsgs.get(i).map_or(&[][..], |c| c.as_ref());
+        let controllen = msg_cmsgs.iter().fold(0, |len, c| len + c.space());
+        if controllen > cmsg_capacity {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        let control = if controllen == 0 {
+            ptr::null()
+        } else {
+            let buffer = &mut data.cmsg_buffers[i] as *mut _ as *mut u8;
+            let mut buf = unsafe { slice::from_raw_parts_mut(buffer, controllen) };
+            for cmsg in msg_cmsgs {
+                unsafe { cmsg.encode_into(&mut buf) };
+            }
+            buffer as *const c_void
+        };
+
+        let hdr = &mut data.items[i];
+        hdr.msg_hdr.msg_name = name;
+        hdr.msg_hdr.msg_namelen = namelen;
+        hdr.msg_hdr.msg_iov = iov.as_ptr() as *const IoVec<&'static [u8]>;
+        hdr.msg_hdr.msg_iovlen = iov.len() as size_t;
+        hdr.msg_hdr.msg_control = control;
+        hdr.msg_hdr.msg_controllen = controllen as size_t;
+        hdr.msg_hdr.msg_flags = 0;
+        hdr.msg_len = 0;
+    }
+
+    let ret = unsafe {
+        ffi::sendmmsg(fd, data.items.as_mut_ptr(), count as c_uint, flags.bits())
+    };
+    let sent = try!(Errno::result(ret)) as usize;
+
+    Ok(MultiSendResults { items: data.items[..sent].iter() })
+}
+
+/// Receive multiple messages from a socket with a single system call.
+///
+/// The message at index `i` is scattered into `slices[i]`; at most
+/// `data.len()` messages are received.  If `timeout` is given, the call
+/// returns once it has elapsed, but note that the timeout is only checked
+/// after each received datagram, as described in the man page.  Returns
+/// an iterator over the received messages, whose source addresses and
+/// control messages refer to the buffers in `data`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/recvmmsg.2.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn recvmmsg<'a, 'b, I, T>(fd: RawFd, data: &'a mut MultiHeaders<T>,
+                              slices: &[I], flags: MsgFlags,
+                              timeout: Option<TimeSpec>)
+                              -> Result<MultiResults<'a, T>>
+    where I: AsRef<[IoVec<&'b mut [u8]>]>
+{
+    let count = ::std::cmp::min(slices.len(), data.items.len());
+    let cmsg_len = data.cmsg_len();
+
+    for (i, slice) in slices.iter().take(count).enumerate() {
+        let iov = slice.as_ref();
+        let control = if cmsg_len == 0 {
+            ptr::null()
+        } else {
+            &mut data.cmsg_buffers[i] as *mut _ as *const c_void
+        };
+
+        let hdr = &mut data.items[i];
+        hdr.msg_hdr.msg_name = &mut data.addresses[i] as *mut _ as *const c_void;
+        hdr.msg_hdr.msg_namelen = mem::size_of::<sockaddr_storage>() as socklen_t;
+        // safe cast to add const-ness
+        hdr.msg_hdr.msg_iov = iov.as_ptr() as *const IoVec<&'static [u8]>;
+        hdr.msg_hdr.msg_iovlen = iov.len() as size_t;
+        hdr.msg_hdr.msg_control = control;
+        hdr.msg_hdr.msg_controllen = cmsg_len as size_t;
+        hdr.msg_hdr.msg_flags = 0;
+        hdr.msg_len = 0;
+    }
+
+    let mut timeout = timeout.map(|t| *t.as_ref());
+    let timeout_ptr = match timeout {
+        Some(ref mut t) => t as *mut timespec,
+        None => ptr::null_mut(),
+    };
+    let ret = unsafe {
+        ffi::recvmmsg(fd, data.items.as_mut_ptr(), count as c_uint,
+                      flags.bits(), timeout_ptr)
+    };
+    let received = try!(Errno::result(ret)) as usize;
+
+    Ok(MultiResults {
+        data: data,
+        current: 0,
+        received: received,
+    })
+}
+
+
 /// Create an endpoint for communication
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/socket.2.html)
@@ -412,6 +820,24 @@ pub fn accept(sockfd: RawFd) -> Result<RawFd> {
     Errno::result(res)
 }
 
+/// Accept a connection on a socket, returning the address of the peer
+/// along with the new socket.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/accept.2.html)
+pub fn accept_from(sockfd: RawFd) -> Result<(RawFd, SockaddrStorage)> {
+    unsafe {
+        let mut addr: sockaddr_storage = mem::zeroed();
+        let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
+
+        let res = try!(Errno::result(ffi::accept(
+            sockfd,
+            &mut addr as *mut _ as *mut sockaddr,
+            &mut len)));
+
+        Ok((res, SockaddrStorage::from_raw(addr, len)))
+    }
+}
+
 /// Accept a connection on a socket
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/accept.2.html)
@@ -466,7 +892,7 @@ pub fn recv(sockfd: RawFd, buf: &mut [u8], flags: MsgFlags) -> Result<usize> {
 /// the number of bytes read and the socket address of the sender.
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/recvmsg.2.html)
-pub fn recvfrom(sockfd: RawFd, buf: &mut [u8]) -> Result<(usize, SockAddr)> {
+pub fn recvfrom(sockfd: RawFd, buf: &mut [u8]) -> Result<(usize, SockaddrStorage)> {
     unsafe {
         let addr: sockaddr_storage = mem::zeroed();
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
@@ -479,8 +905,7 @@ pub fn recvfrom(sockfd: RawFd, buf: &mut [u8]) -> Result<(usize, SockAddr)> {
             mem::transmute(&addr),
             &mut len as *mut socklen_t)));
 
-        sockaddr_storage_to_addr(&addr, len as usize)
-            .map(|addr| (ret as usize, addr))
+        Ok((ret as usize, SockaddrStorage::from_raw(addr, len)))
     }
 }
 
@@ -514,9 +939,158 @@ pub struct linger {
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct ucred {
-    pid: pid_t,
-    uid: uid_t,
-    gid: gid_t,
+    pub pid: pid_t,
+    pub uid: uid_t,
+    pub gid: gid_t,
+}
+
+/// The payload of an `IP_PKTINFO` control message.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct in_pktinfo {
+    /// Index of the interface the packet was received on
+    pub ipi_ifindex: c_int,
+    /// Local address of the packet
+    pub ipi_spec_dst: in_addr,
+    /// Destination address in the packet header
+    pub ipi_addr: in_addr,
+}
+
+/// The payload of an `IPV6_PKTINFO` control message.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/ipv6.7.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct in6_pktinfo {
+    /// Source or destination IPv6 address
+    pub ipi6_addr: in6_addr,
+    /// Index of the interface the packet was sent or received on
+    pub ipi6_ifindex: c_uint,
+}
+
+/// The extended error carried by an `IP_RECVERR` control message.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct sock_extended_err {
+    pub ee_errno: u32,
+    pub ee_origin: u8,
+    pub ee_type: u8,
+    pub ee_code: u8,
+    pub ee_pad: u8,
+    pub ee_info: u32,
+    pub ee_data: u32,
+}
+
+/// Statistics of a TCP connection, as returned by the `TcpInfo` socket
+/// option.  This holds the fields every Linux kernel provides; newer ones
+/// fill in more, which are left out.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/tcp.7.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct tcp_info {
+    pub tcpi_state: u8,
+    pub tcpi_ca_state: u8,
+    pub tcpi_retransmits: u8,
+    pub tcpi_probes: u8,
+    pub tcpi_backoff: u8,
+    pub tcpi_options: u8,
+    /// The `tcpi_snd_wscale` and `tcpi_rcv_wscale` bitfields, see
+    /// `snd_wscale()` and `rcv_wscale()`.
+    pub tcpi_wscale: u8,
+    pub tcpi_flags: u8,
+
+    pub tcpi_rto: u32,
+    pub tcpi_ato: u32,
+    pub tcpi_snd_mss: u32,
+    pub tcpi_rcv_mss: u32,
+
+    pub tcpi_unacked: u32,
+    pub tcpi_sacked: u32,
+    pub tcpi_lost: u32,
+    pub tcpi_retrans: u32,
+    pub tcpi_fackets: u32,
+
+    pub tcpi_last_data_sent: u32,
+    pub tcpi_last_ack_sent: u32,
+    pub tcpi_last_data_recv: u32,
+    pub tcpi_last_ack_recv: u32,
+
+    pub tcpi_pmtu: u32,
+    pub tcpi_rcv_ssthresh: u32,
+    pub tcpi_rtt: u32,
+    pub tcpi_rttvar: u32,
+    pub tcpi_snd_ssthresh: u32,
+    pub tcpi_snd_cwnd: u32,
+    pub tcpi_advmss: u32,
+    pub tcpi_reordering: u32,
+
+    pub tcpi_rcv_rtt: u32,
+    pub tcpi_rcv_space: u32,
+
+    pub tcpi_total_retrans: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl tcp_info {
+    /// The window scale the peer advertised, which applies to the windows
+    /// it sends us and so to our send window (`tcpi_snd_wscale`).
+    #[cfg(target_endian = "little")]
+    pub fn snd_wscale(&self) -> u8 {
+        self.tcpi_wscale & 0xf
+    }
+
+    /// The window scale the peer advertised, which applies to the windows
+    /// it sends us and so to our send window (`tcpi_snd_wscale`).
+    #[cfg(target_endian = "big")]
+    pub fn snd_wscale(&self) -> u8 {
+        self.tcpi_wscale >> 4
+    }
+
+    /// Our own window scale, which applies to the windows we advertise to
+    /// the peer and so to our receive window (`tcpi_rcv_wscale`).
+    #[cfg(target_endian = "little")]
+    pub fn rcv_wscale(&self) -> u8 {
+        self.tcpi_wscale >> 4
+    }
+
+    /// Our own window scale, which applies to the windows we advertise to
+    /// the peer and so to our receive window (`tcpi_rcv_wscale`).
+    #[cfg(target_endian = "big")]
+    pub fn rcv_wscale(&self) -> u8 {
+        self.tcpi_wscale & 0xf
+    }
+}
+
+/// A classic BPF instruction.
+///
+/// [Further reading](https://www.kernel.org/doc/Documentation/networking/filter.txt)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct sock_filter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// A classic BPF program, as attached with the `AttachFilter` socket option.
+/// `filter` has to point to `len` instructions.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct sock_fprog {
+    pub len: c_ushort,
+    pub filter: *mut sock_filter,
 }
 
 /*
@@ -538,6 +1112,8 @@ pub enum SockLevel {
     Udp = IPPROTO_UDP,
     #[cfg(any(target_os = "linux", target_os = "android"))]
     Netlink = SOL_NETLINK,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Packet = SOL_PACKET,
 }
 
 /// Represents a socket option that can be accessed or set. Used as an argument
@@ -575,32 +1151,43 @@ pub fn setsockopt<O: SetSockOpt>(fd: RawFd, opt: O, val: &O::Val) -> Result<()>
 /// Get the address of the peer connected to the socket `fd`.
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/getpeername.2.html)
-pub fn getpeername(fd: RawFd) -> Result<SockAddr> {
+pub fn getpeername(fd: RawFd) -> Result<SockaddrStorage> {
     unsafe {
-        let addr: sockaddr_storage = mem::uninitialized();
+        let addr: sockaddr_storage = mem::zeroed();
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
 
         let ret = ffi::getpeername(fd, mem::transmute(&addr), &mut len);
 
         try!(Errno::result(ret));
 
-        sockaddr_storage_to_addr(&addr, len as usize)
+        Ok(SockaddrStorage::from_raw(addr, len))
     }
 }
 
 /// Get the current address to which the socket `fd` is bound.
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/getsockname.2.html)
-pub fn getsockname(fd: RawFd) -> Result<SockAddr> {
+pub fn getsockname(fd: RawFd) -> Result<SockaddrStorage> {
     unsafe {
-        let addr: sockaddr_storage = mem::uninitialized();
+        let addr: sockaddr_storage = mem::zeroed();
         let mut len = mem::size_of::<sockaddr_storage>() as socklen_t;
 
         let ret = ffi::getsockname(fd, mem::transmute(&addr), &mut len);
 
         try!(Errno::result(ret));
 
-        sockaddr_storage_to_addr(&addr, len as usize)
+        Ok(SockaddrStorage::from_raw(addr, len))
+    }
+}
+
+// The address of a received message, or None if the kernel didn't fill
+// one in.
+fn storage_to_option(addr: sockaddr_storage, len: socklen_t) -> Option<SockaddrStorage> {
+    let addr = SockaddrStorage::from_raw(addr, len);
+    if addr.is_empty() {
+        None
+    } else {
+        Some(addr)
     }
 }
 
@@ -610,6 +1197,10 @@ pub fn getsockname(fd: RawFd) -> Result<SockAddr> {
 /// allocated and valid.  It must be at least as large as all the useful parts
 /// of the structure.  Note that in the case of a `sockaddr_un`, `len` need not
 /// include the terminating null.
+///
+/// Fails with `EAFNOSUPPORT` if the address family can't be represented by
+/// a `SockAddr`; use `SockaddrStorage` to hold such addresses.  Fails with
+/// `EINVAL` if `len` doesn't match the size of an address of the family.
 pub unsafe fn sockaddr_storage_to_addr(
     addr: &sockaddr_storage,
     len: usize) -> Result<SockAddr> {
@@ -620,15 +1211,23 @@ pub unsafe fn sockaddr_storage_to_addr(
 
     match addr.ss_family as c_int {
         consts::AF_INET => {
-            assert!(len as usize == mem::size_of::<sockaddr_in>());
+            if len != mem::size_of::<sockaddr_in>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             let ret = *(addr as *const _ as *const sockaddr_in);
             Ok(SockAddr::Inet(InetAddr::V4(ret)))
         }
         consts::AF_INET6 => {
-            assert!(len as usize == mem::size_of::<sockaddr_in6>());
+            if len != mem::size_of::<sockaddr_in6>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             Ok(SockAddr::Inet(InetAddr::V6((*(addr as *const _ as *const sockaddr_in6)))))
         }
         consts::AF_UNIX => {
+            // The path has to fit into sun_path
+            if len < offset_of!(sockaddr_un, sun_path) || len > mem::size_of::<sockaddr_un>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             let sun = *(addr as *const _ as *const sockaddr_un);
             let pathlen = len - offset_of!(sockaddr_un, sun_path);
             Ok(SockAddr::Unix(UnixAddr(sun, pathlen)))
@@ -636,9 +1235,26 @@ pub unsafe fn sockaddr_storage_to_addr(
         #[cfg(any(target_os = "linux", target_os = "android"))]
         consts::AF_NETLINK => {
             use libc::sockaddr_nl;
+            if len < mem::size_of::<sockaddr_nl>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
             Ok(SockAddr::Netlink(NetlinkAddr(*(addr as *const _ as *const sockaddr_nl))))
         }
-        af => panic!("unexpected address family {}", af),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        consts::AF_PACKET => {
+            // The kernel only fills in as much of sll_addr as the hardware
+            // address needs
+            let addr_offset = offset_of!(sockaddr_ll, sll_addr);
+            if len < addr_offset || len > mem::size_of::<sockaddr_ll>() {
+                return Err(Error::Sys(Errno::EINVAL));
+            }
+            let mut sll = *(addr as *const _ as *const sockaddr_ll);
+            for b in &mut sll.sll_addr[len - addr_offset..] {
+                *b = 0;
+            }
+            Ok(SockAddr::Link(LinkAddr(sll)))
+        }
+        _ => Err(Error::Sys(Errno::EAFNOSUPPORT)),
     }
 }
 
@@ -1,5 +1,7 @@
 use super::addr::{Ipv4Addr, Ipv6Addr};
 use libc::{in_addr, in6_addr, c_uint};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc::{c_int, c_uchar, c_ushort};
 use std::fmt;
 
 #[repr(C)]
@@ -38,3 +40,37 @@ impl ipv6_mreq {
         }
     }
 }
+
+/// Request for `sockopt::PacketAddMembership` and
+/// `sockopt::PacketDropMembership`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man7/packet.7.html)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct packet_mreq {
+    pub mr_ifindex: c_int,
+    pub mr_type: c_ushort,
+    pub mr_alen: c_ushort,
+    pub mr_address: [c_uchar; 8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl packet_mreq {
+    /// Create a request of type `mr_type`, one of the `PACKET_MR_*`
+    /// constants, for interface `ifindex`.  `addr` is the multicast
+    /// hardware address for `PACKET_MR_MULTICAST`; at most 8 bytes of it
+    /// are used.
+    pub fn new(ifindex: c_int, mr_type: c_ushort, addr: &[u8]) -> packet_mreq {
+        let mut mreq = packet_mreq {
+            mr_ifindex: ifindex,
+            mr_type: mr_type,
+            mr_alen: 0,
+            mr_address: [0; 8],
+        };
+        let len = ::std::cmp::min(addr.len(), mreq.mr_address.len());
+        mreq.mr_address[..len].copy_from_slice(&addr[..len]);
+        mreq.mr_alen = len as c_ushort;
+        mreq
+    }
+}
@@ -0,0 +1,638 @@
+//! Netlink message framing
+//!
+//! Netlink sockets exchange streams of messages, each made of an
+//! `nlmsghdr` followed by a family specific header and a list of `rtattr`
+//! attributes.  This module builds and parses such streams, and provides
+//! `NetlinkSocket`, which takes care of sequence numbers, multipart dumps
+//! and acknowledgements.  Typed requests for the routing family live in the
+//! `route` module.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/netlink.7.html)
+use {Errno, Error, Result};
+use libc::c_int;
+use std::{cmp, mem, ptr, slice, str};
+use std::os::unix::io::{AsRawFd, RawFd};
+use sys::socket::{self, sockopt, AddressFamily, SockAddr, SockType, SOCK_CLOEXEC};
+use unistd;
+
+pub mod route;
+
+/// Routing and link updates
+pub const NETLINK_ROUTE: c_int = 0;
+/// Firewalling hook
+pub const NETLINK_FIREWALL: c_int = 3;
+/// Kernel audit
+pub const NETLINK_AUDIT: c_int = 9;
+/// Kernel messages to userspace
+pub const NETLINK_KOBJECT_UEVENT: c_int = 15;
+/// Generic netlink
+pub const NETLINK_GENERIC: c_int = 16;
+
+/// No operation; the message should be ignored
+pub const NLMSG_NOOP: u16 = 1;
+/// An error or, if the error is 0, an acknowledgement
+pub const NLMSG_ERROR: u16 = 2;
+/// The end of a multipart message
+pub const NLMSG_DONE: u16 = 3;
+/// Data was lost
+pub const NLMSG_OVERRUN: u16 = 4;
+
+bitflags!(
+    pub flags NlmFlags: u16 {
+        const NLM_F_REQUEST   = 0x001,
+        const NLM_F_MULTI     = 0x002,
+        const NLM_F_ACK       = 0x004,
+        const NLM_F_ECHO      = 0x008,
+        const NLM_F_DUMP_INTR = 0x010,
+        // Modifiers to GET requests
+        const NLM_F_ROOT      = 0x100,
+        const NLM_F_MATCH     = 0x200,
+        const NLM_F_ATOMIC    = 0x400,
+        const NLM_F_DUMP      = 0x300,
+        // Modifiers to NEW requests
+        const NLM_F_REPLACE   = 0x100,
+        const NLM_F_EXCL      = 0x200,
+        const NLM_F_CREATE    = 0x400,
+        const NLM_F_APPEND    = 0x800,
+    }
+);
+
+/// The header of every netlink message.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct nlmsghdr {
+    pub nlmsg_len: u32,
+    pub nlmsg_type: u16,
+    pub nlmsg_flags: u16,
+    pub nlmsg_seq: u32,
+    pub nlmsg_pid: u32,
+}
+
+/// The payload of an `NLMSG_ERROR` message.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct nlmsgerr {
+    pub error: c_int,
+    pub msg: nlmsghdr,
+}
+
+/// The header of a netlink attribute.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct rtattr {
+    pub rta_len: u16,
+    pub rta_type: u16,
+}
+
+/// Set in the type of an attribute that contains nested attributes.
+pub const NLA_F_NESTED: u16 = 1 << 15;
+
+/// Round `len` up to the 4 byte alignment of netlink messages and
+/// attributes.
+pub fn nlmsg_align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+// Copy a T out of the start of buf, which need not be aligned.
+fn read_struct<T: Copy>(buf: &[u8]) -> Option<T> {
+    if buf.len() < mem::size_of::<T>() {
+        return None;
+    }
+    unsafe {
+        let mut val: T = mem::uninitialized();
+        ptr::copy_nonoverlapping(buf.as_ptr(), &mut val as *mut T as *mut u8,
+                                 mem::size_of::<T>());
+        Some(val)
+    }
+}
+
+// The length of an attribute, if it fits the u16 `rta_len`.
+fn attr_len(len: usize) -> Option<u16> {
+    if len <= u16::max_value() as usize {
+        Some(len as u16)
+    } else {
+        None
+    }
+}
+
+fn struct_bytes<T>(val: &T) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(val as *const T as *const u8, mem::size_of::<T>())
+    }
+}
+
+/*
+ *
+ * ===== Building =====
+ *
+ */
+
+/// Builds a single netlink message: the header, a family specific payload
+/// and attributes, which may be nested.  Attributes that are too long and
+/// unbalanced `nest_start` and `nest_end` calls make `finish` fail with
+/// `EINVAL`.
+///
+/// ```no_run
+/// # use nix::sys::socket::netlink::*;
+/// # use nix::sys::socket::netlink::route::*;
+/// let mut msg = NlMsgBuilder::new(RTM_GETLINK, NLM_F_REQUEST | NLM_F_DUMP);
+/// msg.payload(&ifinfomsg::new(0));
+/// ```
+pub struct NlMsgBuilder {
+    buf: Vec<u8>,
+    nests: Vec<usize>,
+    // Set by an attribute or nest that couldn't be encoded
+    invalid: bool,
+}
+
+impl NlMsgBuilder {
+    /// Start a message of type `ty` with the given flags.
+    pub fn new(ty: u16, flags: NlmFlags) -> NlMsgBuilder {
+        let hdr = nlmsghdr {
+            nlmsg_len: 0,
+            nlmsg_type: ty,
+            nlmsg_flags: flags.bits(),
+            nlmsg_seq: 0,
+            nlmsg_pid: 0,
+        };
+        let mut builder = NlMsgBuilder {
+            buf: Vec::with_capacity(256),
+            nests: Vec::new(),
+            invalid: false,
+        };
+        builder.push(struct_bytes(&hdr));
+        builder
+    }
+
+    // Append bytes, padding the message to the netlink alignment.
+    fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+        let aligned = nlmsg_align(self.buf.len());
+        self.buf.resize(aligned, 0);
+    }
+
+    /// Append the family specific header, such as an `ifinfomsg`.
+    pub fn payload<T>(&mut self, payload: &T) -> &mut NlMsgBuilder {
+        self.push(struct_bytes(payload));
+        self
+    }
+
+    /// Append an attribute with raw data, which must fit the 16 bit length
+    /// of an `rtattr`.
+    pub fn attr(&mut self, ty: u16, data: &[u8]) -> &mut NlMsgBuilder {
+        let len = match attr_len(mem::size_of::<rtattr>() + data.len()) {
+            Some(len) => len,
+            None => {
+                self.invalid = true;
+                return self;
+            },
+        };
+        let hdr = rtattr {
+            rta_len: len,
+            rta_type: ty,
+        };
+        self.buf.extend_from_slice(struct_bytes(&hdr));
+        self.push(data);
+        self
+    }
+
+    /// Append an attribute holding a `u32` in host byte order.
+    pub fn attr_u32(&mut self, ty: u16, val: u32) -> &mut NlMsgBuilder {
+        self.attr(ty, struct_bytes(&val))
+    }
+
+    /// Append an attribute holding a null-terminated string.
+    pub fn attr_str(&mut self, ty: u16, val: &str) -> &mut NlMsgBuilder {
+        let mut data = Vec::with_capacity(val.len() + 1);
+        data.extend_from_slice(val.as_bytes());
+        data.push(0);
+        self.attr(ty, &data)
+    }
+
+    /// Start an attribute containing nested attributes, which is closed
+    /// by the matching `nest_end`.
+    pub fn nest_start(&mut self, ty: u16) -> &mut NlMsgBuilder {
+        self.nests.push(self.buf.len());
+        self.attr(ty | NLA_F_NESTED, &[])
+    }
+
+    /// Close the innermost attribute opened by `nest_start`.  The nested
+    /// attributes must fit the 16 bit length of an `rtattr`.
+    pub fn nest_end(&mut self) -> &mut NlMsgBuilder {
+        let start = match self.nests.pop() {
+            Some(start) => start,
+            None => {
+                self.invalid = true;
+                return self;
+            },
+        };
+        match attr_len(self.buf.len() - start) {
+            Some(len) => self.buf[start..start + 2].copy_from_slice(struct_bytes(&len)),
+            None => self.invalid = true,
+        }
+        self
+    }
+
+    /// The flags of the message.
+    pub fn flags(&self) -> NlmFlags {
+        let hdr: nlmsghdr = read_struct(&self.buf).unwrap();
+        NlmFlags::from_bits_truncate(hdr.nlmsg_flags)
+    }
+
+    /// Finish the message, filling in its length, sequence number and
+    /// port id, and return its bytes.  Several finished messages may be
+    /// concatenated and sent at once.  Fails with `EINVAL` if an attribute
+    /// was too long, or if the `nest_start` and `nest_end` calls don't
+    /// match up.
+    pub fn finish(&self, seq: u32, pid: u32) -> Result<Vec<u8>> {
+        if self.invalid || !self.nests.is_empty() ||
+            self.buf.len() > u32::max_value() as usize {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+        let hdr = nlmsghdr {
+            nlmsg_len: self.buf.len() as u32,
+            nlmsg_seq: seq,
+            nlmsg_pid: pid,
+            .. read_struct::<nlmsghdr>(&self.buf).unwrap()
+        };
+        let mut buf = self.buf.clone();
+        buf[..mem::size_of::<nlmsghdr>()].copy_from_slice(struct_bytes(&hdr));
+        Ok(buf)
+    }
+}
+
+/*
+ *
+ * ===== Parsing =====
+ *
+ */
+
+/// A single netlink message borrowed from a receive buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct NlMsg<'a> {
+    header: nlmsghdr,
+    payload: &'a [u8],
+}
+
+impl<'a> NlMsg<'a> {
+    /// The message type, e.g. `NLMSG_ERROR` or `route::RTM_NEWLINK`.
+    pub fn ty(&self) -> u16 {
+        self.header.nlmsg_type
+    }
+
+    pub fn flags(&self) -> NlmFlags {
+        NlmFlags::from_bits_truncate(self.header.nlmsg_flags)
+    }
+
+    pub fn seq(&self) -> u32 {
+        self.header.nlmsg_seq
+    }
+
+    /// The port id of the sender; 0 for the kernel.
+    pub fn pid(&self) -> u32 {
+        self.header.nlmsg_pid
+    }
+
+    /// Everything following the `nlmsghdr`.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Copy the family specific header out of the start of the payload.
+    /// Returns `None` if the payload is too short.
+    pub fn payload_as<T: Copy>(&self) -> Option<T> {
+        read_struct(self.payload)
+    }
+
+    /// The attributes following a family specific header of type `T`.
+    pub fn attrs<T>(&self) -> RtAttrIter<'a> {
+        let start = cmp::min(nlmsg_align(mem::size_of::<T>()), self.payload.len());
+        RtAttrIter(&self.payload[start..])
+    }
+
+    /// For `NLMSG_ERROR` messages, `Some(Ok(()))` for an acknowledgement
+    /// and `Some(Err(..))` for an error reply.  `None` for other messages.
+    pub fn error(&self) -> Option<Result<()>> {
+        if self.ty() != NLMSG_ERROR {
+            return None;
+        }
+        match self.payload_as::<nlmsgerr>() {
+            Some(nlmsgerr { error: 0, .. }) => Some(Ok(())),
+            Some(err) => Some(Err(Error::Sys(Errno::from_i32(-err.error)))),
+            None => Some(Err(Error::Sys(Errno::EBADMSG))),
+        }
+    }
+}
+
+/// An iterator over the messages in a buffer received from a netlink
+/// socket.  Iteration stops at the first truncated message.
+#[derive(Clone, Debug)]
+pub struct NlMsgIter<'a>(&'a [u8]);
+
+impl<'a> NlMsgIter<'a> {
+    pub fn new(buf: &'a [u8]) -> NlMsgIter<'a> {
+        NlMsgIter(buf)
+    }
+}
+
+impl<'a> Iterator for NlMsgIter<'a> {
+    type Item = NlMsg<'a>;
+
+    fn next(&mut self) -> Option<NlMsg<'a>> {
+        let buf = self.0;
+        let header: nlmsghdr = match read_struct(buf) {
+            Some(header) => header,
+            None => return None,
+        };
+        let len = header.nlmsg_len as usize;
+        if len < mem::size_of::<nlmsghdr>() || len > buf.len() {
+            self.0 = &[];
+            return None;
+        }
+        self.0 = &buf[cmp::min(nlmsg_align(len), buf.len())..];
+
+        Some(NlMsg {
+            header: header,
+            payload: &buf[mem::size_of::<nlmsghdr>()..len],
+        })
+    }
+}
+
+/// A single attribute.
+#[derive(Clone, Copy, Debug)]
+pub struct RtAttr<'a> {
+    ty: u16,
+    data: &'a [u8],
+}
+
+impl<'a> RtAttr<'a> {
+    /// The attribute type, without the `NLA_F_NESTED` flag.
+    pub fn ty(&self) -> u16 {
+        self.ty & !NLA_F_NESTED
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The value of an attribute holding a `u32` in host byte order.
+    pub fn as_u32(&self) -> Option<u32> {
+        read_struct(self.data)
+    }
+
+    /// The value of an attribute holding a string, with any terminating
+    /// null removed.
+    pub fn as_str(&self) -> Option<&'a str> {
+        let data = match self.data.iter().position(|&b| b == 0) {
+            Some(end) => &self.data[..end],
+            None => self.data,
+        };
+        str::from_utf8(data).ok()
+    }
+
+    /// The attributes nested in this one.
+    pub fn nested(&self) -> RtAttrIter<'a> {
+        RtAttrIter(self.data)
+    }
+}
+
+/// An iterator over a list of attributes.
+#[derive(Clone, Debug)]
+pub struct RtAttrIter<'a>(&'a [u8]);
+
+impl<'a> RtAttrIter<'a> {
+    pub fn new(buf: &'a [u8]) -> RtAttrIter<'a> {
+        RtAttrIter(buf)
+    }
+}
+
+impl<'a> Iterator for RtAttrIter<'a> {
+    type Item = RtAttr<'a>;
+
+    fn next(&mut self) -> Option<RtAttr<'a>> {
+        let buf = self.0;
+        let header: rtattr = match read_struct(buf) {
+            Some(header) => header,
+            None => return None,
+        };
+        let len = header.rta_len as usize;
+        if len < mem::size_of::<rtattr>() || len > buf.len() {
+            self.0 = &[];
+            return None;
+        }
+        self.0 = &buf[cmp::min(nlmsg_align(len), buf.len())..];
+
+        Some(RtAttr {
+            ty: header.rta_type,
+            data: &buf[mem::size_of::<rtattr>()..len],
+        })
+    }
+}
+
+/*
+ *
+ * ===== Sockets =====
+ *
+ */
+
+/// A netlink socket that numbers the requests sent on it and collects
+/// their replies.  The socket is closed when dropped.
+pub struct NetlinkSocket {
+    fd: RawFd,
+    seq: u32,
+    pid: u32,
+    buf: Vec<u8>,
+}
+
+impl NetlinkSocket {
+    /// Open a netlink socket of the given protocol, e.g. `NETLINK_ROUTE`,
+    /// and bind it to the multicast groups in the `groups` bitmask.
+    pub fn new(protocol: c_int, groups: u32) -> Result<NetlinkSocket> {
+        let fd = try!(socket::socket(AddressFamily::Netlink, SockType::Raw,
+                                     SOCK_CLOEXEC, protocol));
+        // Owned from here on, so that the socket is closed on error.
+        let mut sock = NetlinkSocket {
+            fd: fd,
+            seq: 0,
+            pid: 0,
+            buf: vec![0; 32768],
+        };
+        try!(socket::bind(fd, &SockAddr::new_netlink(0, groups)));
+        sock.pid = match try!(socket::getsockname(fd)).to_sock_addr() {
+            Ok(SockAddr::Netlink(addr)) => addr.pid(),
+            _ => return Err(Error::Sys(Errno::EAFNOSUPPORT)),
+        };
+        Ok(sock)
+    }
+
+    /// The port id the kernel assigned to this socket.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Subscribe to the multicast group `group`.  Unlike the bitmask given
+    /// to `new`, this works for groups numbered above 32.
+    pub fn add_membership(&self, group: u32) -> Result<()> {
+        socket::setsockopt(self.fd, sockopt::NetlinkAddMembership, &group)
+    }
+
+    /// Unsubscribe from the multicast group `group`.
+    pub fn drop_membership(&self, group: u32) -> Result<()> {
+        socket::setsockopt(self.fd, sockopt::NetlinkDropMembership, &group)
+    }
+
+    /// Send a message, returning the sequence number assigned to it.
+    pub fn send(&mut self, msg: &NlMsgBuilder) -> Result<u32> {
+        self.seq = self.seq.wrapping_add(1);
+        let seq = self.seq;
+        let buf = try!(msg.finish(seq, self.pid));
+        let kernel = SockAddr::new_netlink(0, 0);
+        try!(socket::sendto(self.fd, &buf, &kernel, socket::MsgFlags::empty()));
+        Ok(seq)
+    }
+
+    /// Receive a datagram, returning an iterator over the messages it
+    /// contains.  Use this to read multicast notifications.  The receive
+    /// buffer grows to fit datagrams larger than it.
+    pub fn recv(&mut self) -> Result<NlMsgIter> {
+        // With MSG_TRUNC the kernel returns the full length of the datagram
+        let size = try!(socket::recv(self.fd, &mut self.buf,
+                                     socket::MSG_PEEK | socket::MSG_TRUNC));
+        if size > self.buf.len() {
+            self.buf.resize(size, 0);
+        }
+        let len = try!(socket::recv(self.fd, &mut self.buf, socket::MsgFlags::empty()));
+        Ok(NlMsgIter(&self.buf[..len]))
+    }
+
+    /// Send a request and call `f` with each message of the reply.  Waits
+    /// for the end of a multipart reply, or for the acknowledgement if the
+    /// request has `NLM_F_ACK` set.  Messages with other sequence numbers,
+    /// such as notifications, are skipped.  An `NLMSG_ERROR` reply is
+    /// returned as an error.
+    pub fn request<F>(&mut self, msg: &NlMsgBuilder, mut f: F) -> Result<()>
+        where F: FnMut(NlMsg) -> Result<()>
+    {
+        let ack = msg.flags().contains(NLM_F_ACK);
+        let seq = try!(self.send(msg));
+
+        loop {
+            let mut done = false;
+            for reply in try!(self.recv()) {
+                if reply.seq() != seq {
+                    continue;
+                }
+                match reply.ty() {
+                    NLMSG_NOOP => {},
+                    NLMSG_DONE => done = true,
+                    NLMSG_OVERRUN => return Err(Error::Sys(Errno::ENOBUFS)),
+                    NLMSG_ERROR => {
+                        try!(reply.error().unwrap());
+                        done = true;
+                    },
+                    _ => {
+                        try!(f(reply));
+                        if !ack && !reply.flags().contains(NLM_F_MULTI) {
+                            done = true;
+                        }
+                    },
+                }
+            }
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Drop for NetlinkSocket {
+    fn drop(&mut self) {
+        let _ = unistd::close(self.fd);
+    }
+}
+
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_and_parse() {
+        let mut msg = NlMsgBuilder::new(100, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&[1u8, 2, 3])
+            .attr_u32(1, 42)
+            .nest_start(2)
+            .attr_str(3, "lo")
+            .nest_end();
+        let buf = msg.finish(7, 1234).unwrap();
+        assert_eq!(buf.len() % 4, 0);
+
+        let msgs: Vec<NlMsg> = NlMsgIter::new(&buf).collect();
+        assert_eq!(msgs.len(), 1);
+        let msg = msgs[0];
+        assert_eq!(msg.ty(), 100);
+        assert_eq!(msg.flags(), NLM_F_REQUEST | NLM_F_ACK);
+        assert_eq!(msg.seq(), 7);
+        assert_eq!(msg.pid(), 1234);
+        assert!(msg.error().is_none());
+
+        let attrs: Vec<RtAttr> = msg.attrs::<[u8; 3]>().collect();
+        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs[0].ty(), 1);
+        assert_eq!(attrs[0].as_u32(), Some(42));
+        assert_eq!(attrs[1].ty(), 2);
+        let nested: Vec<RtAttr> = attrs[1].nested().collect();
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].as_str(), Some("lo"));
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut err = NlMsgBuilder::new(NLMSG_ERROR, NlmFlags::empty());
+        err.payload(&nlmsgerr {
+            error: -(Errno::EEXIST as c_int),
+            msg: unsafe { mem::zeroed() },
+        });
+        let mut ack = NlMsgBuilder::new(NLMSG_ERROR, NlmFlags::empty());
+        ack.payload(&nlmsgerr {
+            error: 0,
+            msg: unsafe { mem::zeroed() },
+        });
+        let mut buf = err.finish(1, 0).unwrap();
+        buf.extend_from_slice(&ack.finish(2, 0).unwrap());
+        // A truncated trailing message is ignored
+        buf.extend_from_slice(&[0xff; 8]);
+
+        let msgs: Vec<NlMsg> = NlMsgIter::new(&buf).collect();
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].error(), Some(Err(Error::Sys(Errno::EEXIST))));
+        assert_eq!(msgs[1].error(), Some(Ok(())));
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let mut msg = NlMsgBuilder::new(100, NLM_F_REQUEST);
+        msg.attr(1, &[0; 65536]);
+        assert_eq!(msg.finish(1, 0).err(), Some(Error::Sys(Errno::EINVAL)));
+
+        let mut msg = NlMsgBuilder::new(100, NLM_F_REQUEST);
+        msg.nest_start(1)
+            .attr(2, &[0; 65530])
+            .attr(3, &[0; 64])
+            .nest_end();
+        assert_eq!(msg.finish(1, 0).err(), Some(Error::Sys(Errno::EINVAL)));
+
+        let mut msg = NlMsgBuilder::new(100, NLM_F_REQUEST);
+        msg.nest_end();
+        assert_eq!(msg.finish(1, 0).err(), Some(Error::Sys(Errno::EINVAL)));
+
+        let mut msg = NlMsgBuilder::new(100, NLM_F_REQUEST);
+        msg.nest_start(1);
+        assert_eq!(msg.finish(1, 0).err(), Some(Error::Sys(Errno::EINVAL)));
+    }
+}
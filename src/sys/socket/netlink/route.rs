@@ -0,0 +1,572 @@
+//! Typed requests for the `NETLINK_ROUTE` family
+//!
+//! `RtNetlink` lists and changes network interfaces, their addresses and
+//! the routing tables, like the `ip` command does.  To follow changes
+//! instead, open a `NetlinkSocket` bound to the `RTMGRP_*` groups and parse
+//! the notifications with `Link::parse`, `Address::parse` and
+//! `Route::parse`.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/rtnetlink.7.html)
+use {Errno, Error, Result};
+use libc::c_int;
+use net::if_::{InterfaceFlags, IFF_UP};
+use std::net::{self, IpAddr};
+use sys::socket::consts;
+use super::*;
+
+// Message types
+pub const RTM_NEWLINK: u16 = 16;
+pub const RTM_DELLINK: u16 = 17;
+pub const RTM_GETLINK: u16 = 18;
+pub const RTM_SETLINK: u16 = 19;
+pub const RTM_NEWADDR: u16 = 20;
+pub const RTM_DELADDR: u16 = 21;
+pub const RTM_GETADDR: u16 = 22;
+pub const RTM_NEWROUTE: u16 = 24;
+pub const RTM_DELROUTE: u16 = 25;
+pub const RTM_GETROUTE: u16 = 26;
+
+// Link attributes
+pub const IFLA_ADDRESS: u16 = 1;
+pub const IFLA_BROADCAST: u16 = 2;
+pub const IFLA_IFNAME: u16 = 3;
+pub const IFLA_MTU: u16 = 4;
+pub const IFLA_LINK: u16 = 5;
+pub const IFLA_MASTER: u16 = 10;
+pub const IFLA_LINKINFO: u16 = 18;
+pub const IFLA_NET_NS_PID: u16 = 19;
+pub const IFLA_NET_NS_FD: u16 = 28;
+
+// Attributes nested in IFLA_LINKINFO
+pub const IFLA_INFO_KIND: u16 = 1;
+pub const IFLA_INFO_DATA: u16 = 2;
+
+// Attributes nested in the IFLA_INFO_DATA of a veth link
+pub const VETH_INFO_PEER: u16 = 1;
+
+// Address attributes
+pub const IFA_ADDRESS: u16 = 1;
+pub const IFA_LOCAL: u16 = 2;
+pub const IFA_LABEL: u16 = 3;
+pub const IFA_BROADCAST: u16 = 4;
+
+// Route attributes
+pub const RTA_DST: u16 = 1;
+pub const RTA_SRC: u16 = 2;
+pub const RTA_IIF: u16 = 3;
+pub const RTA_OIF: u16 = 4;
+pub const RTA_GATEWAY: u16 = 5;
+pub const RTA_PRIORITY: u16 = 6;
+pub const RTA_PREFSRC: u16 = 7;
+pub const RTA_TABLE: u16 = 15;
+
+// Routing tables
+pub const RT_TABLE_UNSPEC: u8 = 0;
+pub const RT_TABLE_MAIN: u8 = 254;
+pub const RT_TABLE_LOCAL: u8 = 255;
+
+// Route origins
+pub const RTPROT_UNSPEC: u8 = 0;
+pub const RTPROT_KERNEL: u8 = 2;
+pub const RTPROT_BOOT: u8 = 3;
+pub const RTPROT_STATIC: u8 = 4;
+
+// Route scopes
+pub const RT_SCOPE_UNIVERSE: u8 = 0;
+pub const RT_SCOPE_LINK: u8 = 253;
+pub const RT_SCOPE_HOST: u8 = 254;
+
+// Route types
+pub const RTN_UNICAST: u8 = 1;
+pub const RTN_LOCAL: u8 = 2;
+pub const RTN_BROADCAST: u8 = 3;
+
+// Multicast group bitmasks, for `NetlinkSocket::new`
+pub const RTMGRP_LINK: u32 = 0x1;
+pub const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+pub const RTMGRP_IPV4_ROUTE: u32 = 0x40;
+pub const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+pub const RTMGRP_IPV6_ROUTE: u32 = 0x400;
+
+// Multicast group numbers, for `NetlinkSocket::add_membership`
+pub const RTNLGRP_LINK: u32 = 1;
+pub const RTNLGRP_IPV4_IFADDR: u32 = 5;
+pub const RTNLGRP_IPV4_ROUTE: u32 = 7;
+pub const RTNLGRP_IPV6_IFADDR: u32 = 9;
+pub const RTNLGRP_IPV6_ROUTE: u32 = 11;
+
+/// The family specific header of link messages.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ifinfomsg {
+    pub ifi_family: u8,
+    pub __ifi_pad: u8,
+    pub ifi_type: u16,
+    pub ifi_index: c_int,
+    pub ifi_flags: u32,
+    pub ifi_change: u32,
+}
+
+impl ifinfomsg {
+    /// A header selecting interface `index`, or all interfaces if 0.
+    pub fn new(index: u32) -> ifinfomsg {
+        ifinfomsg {
+            ifi_family: 0,
+            __ifi_pad: 0,
+            ifi_type: 0,
+            ifi_index: index as c_int,
+            ifi_flags: 0,
+            ifi_change: 0,
+        }
+    }
+}
+
+/// The family specific header of address messages.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ifaddrmsg {
+    pub ifa_family: u8,
+    pub ifa_prefixlen: u8,
+    pub ifa_flags: u8,
+    pub ifa_scope: u8,
+    pub ifa_index: u32,
+}
+
+/// The family specific header of route messages.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct rtmsg {
+    pub rtm_family: u8,
+    pub rtm_dst_len: u8,
+    pub rtm_src_len: u8,
+    pub rtm_tos: u8,
+    pub rtm_table: u8,
+    pub rtm_protocol: u8,
+    pub rtm_scope: u8,
+    pub rtm_type: u8,
+    pub rtm_flags: u32,
+}
+
+// The address family and bytes of an IP address, in network byte order.
+fn ip_to_bytes(addr: &IpAddr) -> (u8, Vec<u8>) {
+    match *addr {
+        IpAddr::V4(ref a) => (consts::AF_INET as u8, a.octets().to_vec()),
+        IpAddr::V6(ref a) => (consts::AF_INET6 as u8, a.octets().to_vec()),
+    }
+}
+
+fn bytes_to_ip(family: u8, data: &[u8]) -> Option<IpAddr> {
+    match (family as c_int, data.len()) {
+        (consts::AF_INET, 4) => {
+            Some(IpAddr::V4(net::Ipv4Addr::new(data[0], data[1], data[2], data[3])))
+        },
+        (consts::AF_INET6, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(data);
+            Some(IpAddr::V6(net::Ipv6Addr::from(octets)))
+        },
+        _ => None,
+    }
+}
+
+/// A network interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// Interface index
+    pub index: u32,
+    /// Interface name
+    pub name: String,
+    /// Interface flags
+    pub flags: InterfaceFlags,
+    /// Maximum transmission unit
+    pub mtu: Option<u32>,
+    /// Hardware address; empty if the interface has none
+    pub address: Vec<u8>,
+    /// Kind of a virtual interface, e.g. "veth" or "bridge"
+    pub kind: Option<String>,
+}
+
+impl Link {
+    /// Parse an `RTM_NEWLINK` or `RTM_DELLINK` message.
+    pub fn parse(msg: &NlMsg) -> Option<Link> {
+        if msg.ty() != RTM_NEWLINK && msg.ty() != RTM_DELLINK {
+            return None;
+        }
+        let hdr = match msg.payload_as::<ifinfomsg>() {
+            Some(hdr) => hdr,
+            None => return None,
+        };
+        let mut link = Link {
+            index: hdr.ifi_index as u32,
+            name: String::new(),
+            flags: InterfaceFlags::from_bits_truncate(hdr.ifi_flags),
+            mtu: None,
+            address: Vec::new(),
+            kind: None,
+        };
+        for attr in msg.attrs::<ifinfomsg>() {
+            match attr.ty() {
+                IFLA_IFNAME => link.name = attr.as_str().unwrap_or("").to_owned(),
+                IFLA_MTU => link.mtu = attr.as_u32(),
+                IFLA_ADDRESS => link.address = attr.data().to_vec(),
+                IFLA_LINKINFO => {
+                    link.kind = attr.nested()
+                        .find(|info| info.ty() == IFLA_INFO_KIND)
+                        .and_then(|info| info.as_str())
+                        .map(|kind| kind.to_owned());
+                },
+                _ => {},
+            }
+        }
+        Some(link)
+    }
+
+    /// Whether the interface is up.
+    pub fn is_up(&self) -> bool {
+        self.flags.contains(IFF_UP)
+    }
+}
+
+/// An address assigned to a network interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Address {
+    /// Index of the interface
+    pub index: u32,
+    /// Length of the network prefix
+    pub prefix_len: u8,
+    /// Scope, one of `RT_SCOPE_*`
+    pub scope: u8,
+    /// Address of the interface; the peer address for point-to-point
+    /// interfaces
+    pub address: Option<IpAddr>,
+    /// Local address of the interface
+    pub local: Option<IpAddr>,
+    /// Label of the address
+    pub label: Option<String>,
+}
+
+impl Address {
+    /// Parse an `RTM_NEWADDR` or `RTM_DELADDR` message.
+    pub fn parse(msg: &NlMsg) -> Option<Address> {
+        if msg.ty() != RTM_NEWADDR && msg.ty() != RTM_DELADDR {
+            return None;
+        }
+        let hdr = match msg.payload_as::<ifaddrmsg>() {
+            Some(hdr) => hdr,
+            None => return None,
+        };
+        let mut addr = Address {
+            index: hdr.ifa_index,
+            prefix_len: hdr.ifa_prefixlen,
+            scope: hdr.ifa_scope,
+            address: None,
+            local: None,
+            label: None,
+        };
+        for attr in msg.attrs::<ifaddrmsg>() {
+            match attr.ty() {
+                IFA_ADDRESS => addr.address = bytes_to_ip(hdr.ifa_family, attr.data()),
+                IFA_LOCAL => addr.local = bytes_to_ip(hdr.ifa_family, attr.data()),
+                IFA_LABEL => addr.label = attr.as_str().map(|l| l.to_owned()),
+                _ => {},
+            }
+        }
+        Some(addr)
+    }
+}
+
+/// An entry of a routing table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    /// Address family
+    pub family: u8,
+    /// Routing table, one of `RT_TABLE_*`
+    pub table: u8,
+    /// Origin, one of `RTPROT_*`
+    pub protocol: u8,
+    /// Scope, one of `RT_SCOPE_*`
+    pub scope: u8,
+    /// Type, one of `RTN_*`
+    pub kind: u8,
+    /// Destination network, or `None` for the default route
+    pub dst: Option<IpAddr>,
+    /// Length of the destination prefix
+    pub dst_len: u8,
+    /// Next hop
+    pub gateway: Option<IpAddr>,
+    /// Index of the outgoing interface
+    pub oif: Option<u32>,
+    /// Metric
+    pub priority: Option<u32>,
+}
+
+impl Route {
+    /// Parse an `RTM_NEWROUTE` or `RTM_DELROUTE` message.
+    pub fn parse(msg: &NlMsg) -> Option<Route> {
+        if msg.ty() != RTM_NEWROUTE && msg.ty() != RTM_DELROUTE {
+            return None;
+        }
+        let hdr = match msg.payload_as::<rtmsg>() {
+            Some(hdr) => hdr,
+            None => return None,
+        };
+        let mut route = Route {
+            family: hdr.rtm_family,
+            table: hdr.rtm_table,
+            protocol: hdr.rtm_protocol,
+            scope: hdr.rtm_scope,
+            kind: hdr.rtm_type,
+            dst: None,
+            dst_len: hdr.rtm_dst_len,
+            gateway: None,
+            oif: None,
+            priority: None,
+        };
+        for attr in msg.attrs::<rtmsg>() {
+            match attr.ty() {
+                RTA_DST => route.dst = bytes_to_ip(hdr.rtm_family, attr.data()),
+                RTA_GATEWAY => route.gateway = bytes_to_ip(hdr.rtm_family, attr.data()),
+                RTA_OIF => route.oif = attr.as_u32(),
+                RTA_PRIORITY => route.priority = attr.as_u32(),
+                RTA_TABLE => {
+                    if let Some(table) = attr.as_u32() {
+                        if table < 256 {
+                            route.table = table as u8;
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+        Some(route)
+    }
+}
+
+/// A client for the `NETLINK_ROUTE` family.
+pub struct RtNetlink {
+    sock: NetlinkSocket,
+}
+
+impl RtNetlink {
+    /// Open a routing netlink socket that isn't subscribed to any groups.
+    pub fn new() -> Result<RtNetlink> {
+        Ok(RtNetlink { sock: try!(NetlinkSocket::new(NETLINK_ROUTE, 0)) })
+    }
+
+    /// The underlying socket.
+    pub fn socket(&mut self) -> &mut NetlinkSocket {
+        &mut self.sock
+    }
+
+    // Send a request and collect the replies `parse` accepts.
+    fn dump<T, F>(&mut self, msg: &NlMsgBuilder, parse: F) -> Result<Vec<T>>
+        where F: Fn(&NlMsg) -> Option<T>
+    {
+        let mut items = Vec::new();
+        try!(self.sock.request(msg, |reply| {
+            if let Some(item) = parse(&reply) {
+                items.push(item);
+            }
+            Ok(())
+        }));
+        Ok(items)
+    }
+
+    // Send a request that only expects an acknowledgement.
+    fn ack(&mut self, msg: &NlMsgBuilder) -> Result<()> {
+        self.sock.request(msg, |_| Ok(()))
+    }
+
+    /// List all network interfaces.
+    pub fn links(&mut self) -> Result<Vec<Link>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETLINK, NLM_F_REQUEST | NLM_F_DUMP);
+        msg.payload(&ifinfomsg::new(0));
+        self.dump(&msg, Link::parse)
+    }
+
+    /// Look up a network interface by name.  Fails with `ENODEV` if there
+    /// is no such interface.
+    pub fn link_by_name(&mut self, name: &str) -> Result<Link> {
+        let mut msg = NlMsgBuilder::new(RTM_GETLINK, NLM_F_REQUEST);
+        msg.payload(&ifinfomsg::new(0))
+            .attr_str(IFLA_IFNAME, name);
+        let mut links = try!(self.dump(&msg, Link::parse));
+        links.pop().ok_or(Error::Sys(Errno::ENODEV))
+    }
+
+    // Change the flags in `change` of interface `index` to `flags`.
+    fn set_link_flags(&mut self, index: u32, flags: InterfaceFlags,
+                      change: InterfaceFlags) -> Result<()> {
+        let mut hdr = ifinfomsg::new(index);
+        hdr.ifi_flags = flags.bits();
+        hdr.ifi_change = change.bits();
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&hdr);
+        self.ack(&msg)
+    }
+
+    /// Bring interface `index` up or down.
+    pub fn set_link_up(&mut self, index: u32, up: bool) -> Result<()> {
+        let flags = if up { IFF_UP } else { InterfaceFlags::empty() };
+        self.set_link_flags(index, flags, IFF_UP)
+    }
+
+    /// Set the MTU of interface `index`.
+    pub fn set_link_mtu(&mut self, index: u32, mtu: u32) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&ifinfomsg::new(index))
+            .attr_u32(IFLA_MTU, mtu);
+        self.ack(&msg)
+    }
+
+    /// Rename interface `index`, which must be down.
+    pub fn set_link_name(&mut self, index: u32, name: &str) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&ifinfomsg::new(index))
+            .attr_str(IFLA_IFNAME, name);
+        self.ack(&msg)
+    }
+
+    /// Move interface `index` into the network namespace of process `pid`.
+    pub fn set_link_netns_pid(&mut self, index: u32, pid: u32) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&ifinfomsg::new(index))
+            .attr_u32(IFLA_NET_NS_PID, pid);
+        self.ack(&msg)
+    }
+
+    /// Create a pair of connected virtual Ethernet interfaces.
+    pub fn create_veth(&mut self, name: &str, peer: &str) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK,
+                                        NLM_F_REQUEST | NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL);
+        msg.payload(&ifinfomsg::new(0))
+            .attr_str(IFLA_IFNAME, name)
+            .nest_start(IFLA_LINKINFO)
+                .attr_str(IFLA_INFO_KIND, "veth")
+                .nest_start(IFLA_INFO_DATA)
+                    .nest_start(VETH_INFO_PEER)
+                        .payload(&ifinfomsg::new(0))
+                        .attr_str(IFLA_IFNAME, peer)
+                    .nest_end()
+                .nest_end()
+            .nest_end();
+        self.ack(&msg)
+    }
+
+    /// Delete interface `index`.
+    pub fn delete_link(&mut self, index: u32) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_DELLINK, NLM_F_REQUEST | NLM_F_ACK);
+        msg.payload(&ifinfomsg::new(index));
+        self.ack(&msg)
+    }
+
+    /// List the addresses of all interfaces.
+    pub fn addresses(&mut self) -> Result<Vec<Address>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETADDR, NLM_F_REQUEST | NLM_F_DUMP);
+        msg.payload(&ifaddrmsg {
+            ifa_family: 0,
+            ifa_prefixlen: 0,
+            ifa_flags: 0,
+            ifa_scope: 0,
+            ifa_index: 0,
+        });
+        self.dump(&msg, Address::parse)
+    }
+
+    fn address_msg(ty: u16, flags: NlmFlags, index: u32, addr: &IpAddr,
+                   prefix_len: u8) -> NlMsgBuilder {
+        let (family, bytes) = ip_to_bytes(addr);
+        let mut msg = NlMsgBuilder::new(ty, flags);
+        msg.payload(&ifaddrmsg {
+            ifa_family: family,
+            ifa_prefixlen: prefix_len,
+            ifa_flags: 0,
+            ifa_scope: RT_SCOPE_UNIVERSE,
+            ifa_index: index,
+        })
+            .attr(IFA_LOCAL, &bytes)
+            .attr(IFA_ADDRESS, &bytes);
+        msg
+    }
+
+    /// Assign `addr` with a network prefix of `prefix_len` bits to
+    /// interface `index`.
+    pub fn add_address(&mut self, index: u32, addr: &IpAddr, prefix_len: u8) -> Result<()> {
+        let msg = RtNetlink::address_msg(RTM_NEWADDR,
+                                         NLM_F_REQUEST | NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL,
+                                         index, addr, prefix_len);
+        self.ack(&msg)
+    }
+
+    /// Remove `addr` from interface `index`.
+    pub fn del_address(&mut self, index: u32, addr: &IpAddr, prefix_len: u8) -> Result<()> {
+        let msg = RtNetlink::address_msg(RTM_DELADDR, NLM_F_REQUEST | NLM_F_ACK,
+                                         index, addr, prefix_len);
+        self.ack(&msg)
+    }
+
+    /// List the IPv4 and IPv6 routes of all tables.
+    pub fn routes(&mut self) -> Result<Vec<Route>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETROUTE, NLM_F_REQUEST | NLM_F_DUMP);
+        msg.payload(&rtmsg {
+            rtm_family: 0,
+            rtm_dst_len: 0,
+            rtm_src_len: 0,
+            rtm_tos: 0,
+            rtm_table: RT_TABLE_UNSPEC,
+            rtm_protocol: RTPROT_UNSPEC,
+            rtm_scope: RT_SCOPE_UNIVERSE,
+            rtm_type: 0,
+            rtm_flags: 0,
+        });
+        self.dump(&msg, Route::parse)
+    }
+
+    fn route_msg(ty: u16, flags: NlmFlags, dst: &IpAddr, dst_len: u8,
+                 gateway: Option<&IpAddr>, oif: Option<u32>) -> NlMsgBuilder {
+        let (family, dst_bytes) = ip_to_bytes(dst);
+        // Routes without a gateway are directly reachable on the link
+        let scope = if gateway.is_some() { RT_SCOPE_UNIVERSE } else { RT_SCOPE_LINK };
+        let mut msg = NlMsgBuilder::new(ty, flags);
+        msg.payload(&rtmsg {
+            rtm_family: family,
+            rtm_dst_len: dst_len,
+            rtm_src_len: 0,
+            rtm_tos: 0,
+            rtm_table: RT_TABLE_MAIN,
+            rtm_protocol: RTPROT_BOOT,
+            rtm_scope: scope,
+            rtm_type: RTN_UNICAST,
+            rtm_flags: 0,
+        });
+        if dst_len > 0 {
+            msg.attr(RTA_DST, &dst_bytes);
+        }
+        if let Some(gateway) = gateway {
+            msg.attr(RTA_GATEWAY, &ip_to_bytes(gateway).1);
+        }
+        if let Some(oif) = oif {
+            msg.attr_u32(RTA_OIF, oif);
+        }
+        msg
+    }
+
+    /// Add a route to the main table for the network `dst`/`dst_len`,
+    /// through `gateway` and/or interface `oif`.  A `dst_len` of 0 adds a
+    /// default route.
+    pub fn add_route(&mut self, dst: &IpAddr, dst_len: u8, gateway: Option<&IpAddr>,
+                     oif: Option<u32>) -> Result<()> {
+        let msg = RtNetlink::route_msg(RTM_NEWROUTE,
+                                       NLM_F_REQUEST | NLM_F_ACK | NLM_F_CREATE | NLM_F_EXCL,
+                                       dst, dst_len, gateway, oif);
+        self.ack(&msg)
+    }
+
+    /// Delete a route added with the same arguments by `add_route`.
+    pub fn del_route(&mut self, dst: &IpAddr, dst_len: u8, gateway: Option<&IpAddr>,
+                     oif: Option<u32>) -> Result<()> {
+        let msg = RtNetlink::route_msg(RTM_DELROUTE, NLM_F_REQUEST | NLM_F_ACK,
+                                       dst, dst_len, gateway, oif);
+        self.ack(&msg)
+    }
+}
@@ -4,7 +4,9 @@ use sys::time::TimeVal;
 use libc::{c_int, uint8_t, c_void, socklen_t};
 #[cfg(target_os = "linux")]
 use libc::sockaddr_in;
+use std::ffi::{OsStr, OsString};
 use std::mem;
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::io::RawFd;
 
 macro_rules! setsockopt_impl {
@@ -85,6 +87,18 @@ macro_rules! sockopt_impl {
         sockopt_impl!(Both, $name, $level, $flag, usize, GetUsize, SetUsize);
     };
 
+    (GetOnly, $name:ident, $level:path, $flag:path, OsString<$array:ty>) => {
+        sockopt_impl!(GetOnly, $name, $level, $flag, OsString, GetOsString<$array>);
+    };
+
+    (SetOnly, $name:ident, $level:path, $flag:path, OsString) => {
+        sockopt_impl!(SetOnly, $name, $level, $flag, OsString, SetOsString);
+    };
+
+    (Both, $name:ident, $level:path, $flag:path, OsString<$array:ty>) => {
+        sockopt_impl!(Both, $name, $level, $flag, OsString, GetOsString<$array>, SetOsString);
+    };
+
     /*
      * Matchers with generic getter types must be placed at the end, so
      * they'll only match _after_ specialized matchers fail
@@ -176,6 +190,76 @@ sockopt_impl!(GetOnly, SockType, consts::SOL_SOCKET, consts::SO_TYPE, super::Soc
 sockopt_impl!(GetOnly, AcceptConn, consts::SOL_SOCKET, consts::SO_ACCEPTCONN, bool);
 #[cfg(target_os = "linux")]
 sockopt_impl!(GetOnly, OriginalDst, consts::SOL_IP, consts::SO_ORIGINAL_DST, sockaddr_in);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, NetlinkAddMembership, consts::SOL_NETLINK, consts::NETLINK_ADD_MEMBERSHIP, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, NetlinkDropMembership, consts::SOL_NETLINK, consts::NETLINK_DROP_MEMBERSHIP, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, PacketAddMembership, consts::SOL_PACKET, consts::PACKET_ADD_MEMBERSHIP, super::packet_mreq);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, PacketDropMembership, consts::SOL_PACKET, consts::PACKET_DROP_MEMBERSHIP, super::packet_mreq);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, PacketFanout, consts::SOL_PACKET, consts::PACKET_FANOUT, u32);
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(target_arch="arm")))]
+sockopt_impl!(Both, PassCred, consts::SOL_SOCKET, consts::SO_PASSCRED, bool);
+sockopt_impl!(Both, ReceiveTimestamp, consts::SOL_SOCKET, consts::SO_TIMESTAMP, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, ReceiveTimestampNs, consts::SOL_SOCKET, consts::SO_TIMESTAMPNS, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Ipv4PacketInfo, consts::IPPROTO_IP, consts::IP_PKTINFO, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Ipv4RecvTtl, consts::IPPROTO_IP, consts::IP_RECVTTL, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Ipv4RecvErr, consts::IPPROTO_IP, consts::IP_RECVERR, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Ipv6RecvPacketInfo, consts::IPPROTO_IPV6, consts::IPV6_RECVPKTINFO, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpKeepInterval, consts::IPPROTO_TCP, consts::TCP_KEEPINTVL, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpKeepCount, consts::IPPROTO_TCP, consts::TCP_KEEPCNT, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpUserTimeout, consts::IPPROTO_TCP, consts::TCP_USER_TIMEOUT, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpCork, consts::IPPROTO_TCP, consts::TCP_CORK, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpQuickAck, consts::IPPROTO_TCP, consts::TCP_QUICKACK, bool);
+// TCP_CA_NAME_MAX is 16
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpCongestion, consts::IPPROTO_TCP, consts::TCP_CONGESTION, OsString<[u8; 16]>);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, TcpFastOpen, consts::IPPROTO_TCP, consts::TCP_FASTOPEN, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(GetOnly, TcpInfo, consts::IPPROTO_TCP, consts::TCP_INFO, super::tcp_info, GetPartialStruct<super::tcp_info>);
+sockopt_impl!(Both, IpTos, consts::IPPROTO_IP, consts::IP_TOS, i32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Ipv6TClass, consts::IPPROTO_IPV6, consts::IPV6_TCLASS, i32);
+sockopt_impl!(Both, Ipv6V6Only, consts::IPPROTO_IPV6, consts::IPV6_V6ONLY, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, IpTransparent, consts::IPPROTO_IP, consts::IP_TRANSPARENT, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, IpFreebind, consts::IPPROTO_IP, consts::IP_FREEBIND, bool);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Mark, consts::SOL_SOCKET, consts::SO_MARK, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, Priority, consts::SOL_SOCKET, consts::SO_PRIORITY, i32);
+// IFNAMSIZ is 16
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, BindToDevice, consts::SOL_SOCKET, consts::SO_BINDTODEVICE, OsString<[u8; 16]>);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(GetOnly, Domain, consts::SOL_SOCKET, consts::SO_DOMAIN, i32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(GetOnly, Protocol, consts::SOL_SOCKET, consts::SO_PROTOCOL, i32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, PeekOffset, consts::SOL_SOCKET, consts::SO_PEEK_OFF, i32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, BusyPoll, consts::SOL_SOCKET, consts::SO_BUSY_POLL, u32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, AttachFilter, consts::SOL_SOCKET, consts::SO_ATTACH_FILTER, super::sock_fprog);
+// The value is ignored
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(SetOnly, DetachFilter, consts::SOL_SOCKET, consts::SO_DETACH_FILTER, i32);
+#[cfg(any(target_os = "linux", target_os = "android"))]
+sockopt_impl!(Both, LockFilter, consts::SOL_SOCKET, consts::SO_LOCK_FILTER, bool);
 
 /*
  *
@@ -241,6 +325,35 @@ impl<'a, T> Set<'a, T> for SetStruct<'a, T> {
     }
 }
 
+/// Getter for structs the kernel may return a prefix of, like `tcp_info`,
+/// which grows with new kernel versions.  The rest stays zeroed.
+struct GetPartialStruct<T> {
+    len: socklen_t,
+    val: T,
+}
+
+impl<T> Get<T> for GetPartialStruct<T> {
+    unsafe fn blank() -> Self {
+        GetPartialStruct {
+            len: mem::size_of::<T>() as socklen_t,
+            val: mem::zeroed(),
+        }
+    }
+
+    unsafe fn ffi_ptr(&mut self) -> *mut c_void {
+        mem::transmute(&mut self.val)
+    }
+
+    unsafe fn ffi_len(&mut self) -> *mut socklen_t {
+        mem::transmute(&mut self.len)
+    }
+
+    unsafe fn unwrap(self) -> T {
+        assert!(self.len as usize <= mem::size_of::<T>(), "invalid getsockopt implementation");
+        self.val
+    }
+}
+
 struct GetBool {
     len: socklen_t,
     val: c_int,
@@ -376,6 +489,54 @@ impl<'a> Set<'a, usize> for SetUsize {
     }
 }
 
+/// Getter for string options, read into the byte array `T` and cut at the
+/// first null byte.
+struct GetOsString<T: AsMut<[u8]>> {
+    len: socklen_t,
+    val: T,
+}
+
+impl<T: AsMut<[u8]>> Get<OsString> for GetOsString<T> {
+    unsafe fn blank() -> Self {
+        GetOsString {
+            len: mem::size_of::<T>() as socklen_t,
+            val: mem::zeroed(),
+        }
+    }
+
+    unsafe fn ffi_ptr(&mut self) -> *mut c_void {
+        self.val.as_mut().as_mut_ptr() as *mut c_void
+    }
+
+    unsafe fn ffi_len(&mut self) -> *mut socklen_t {
+        mem::transmute(&mut self.len)
+    }
+
+    unsafe fn unwrap(mut self) -> OsString {
+        let bytes = &self.val.as_mut()[..self.len as usize];
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        OsStr::from_bytes(&bytes[..end]).to_owned()
+    }
+}
+
+struct SetOsString<'a> {
+    val: &'a OsStr,
+}
+
+impl<'a> Set<'a, OsString> for SetOsString<'a> {
+    fn new(val: &'a OsString) -> SetOsString<'a> {
+        SetOsString { val: val.as_os_str() }
+    }
+
+    unsafe fn ffi_ptr(&self) -> *const c_void {
+        self.val.as_bytes().as_ptr() as *const c_void
+    }
+
+    unsafe fn ffi_len(&self) -> socklen_t {
+        self.val.len() as socklen_t
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(all(target_os = "linux", not(target_arch = "arm")))]
@@ -429,4 +590,69 @@ mod test {
         assert!(s_listening2);
         close(s).unwrap();
     }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn can_get_and_set_string_options() {
+        use super::super::*;
+        use ::unistd::close;
+        use std::ffi::OsString;
+
+        let s = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), 0).unwrap();
+        assert!(getsockopt(s, super::TcpCongestion).unwrap().len() > 0);
+        // Reno is built into every kernel
+        setsockopt(s, super::TcpCongestion, &OsString::from("reno")).unwrap();
+        assert_eq!(getsockopt(s, super::TcpCongestion).unwrap(), OsString::from("reno"));
+        assert!(setsockopt(s, super::TcpCongestion, &OsString::from("nonexistent")).is_err());
+        assert_eq!(getsockopt(s, super::BindToDevice).unwrap(), OsString::new());
+        close(s).unwrap();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn can_get_tcp_info() {
+        use super::super::*;
+        use ::unistd::close;
+
+        let listener = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), 0).unwrap();
+        let addr = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+        bind(listener, &addr).unwrap();
+        listen(listener, 1).unwrap();
+        let addr = getsockname(listener).unwrap().to_sock_addr().unwrap();
+
+        let s = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), 0).unwrap();
+        connect(s, &addr).unwrap();
+        let info = getsockopt(s, super::TcpInfo).unwrap();
+        // TCP_ESTABLISHED
+        assert_eq!(info.tcpi_state, 1);
+        assert!(info.tcpi_snd_mss > 0);
+        close(s).unwrap();
+        close(listener).unwrap();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn can_get_and_set_int_options() {
+        use super::super::*;
+        use ::unistd::close;
+
+        let s = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), 0).unwrap();
+        assert_eq!(getsockopt(s, super::Domain).unwrap(), AF_INET);
+        assert_eq!(getsockopt(s, super::Protocol).unwrap(), IPPROTO_TCP);
+        setsockopt(s, super::TcpKeepInterval, &7).unwrap();
+        assert_eq!(getsockopt(s, super::TcpKeepInterval).unwrap(), 7);
+        setsockopt(s, super::TcpKeepCount, &3).unwrap();
+        assert_eq!(getsockopt(s, super::TcpKeepCount).unwrap(), 3);
+        setsockopt(s, super::TcpUserTimeout, &5000).unwrap();
+        assert_eq!(getsockopt(s, super::TcpUserTimeout).unwrap(), 5000);
+        setsockopt(s, super::TcpCork, &true).unwrap();
+        assert!(getsockopt(s, super::TcpCork).unwrap());
+        setsockopt(s, super::IpTos, &0x10).unwrap();
+        assert_eq!(getsockopt(s, super::IpTos).unwrap(), 0x10);
+        setsockopt(s, super::Priority, &3).unwrap();
+        assert_eq!(getsockopt(s, super::Priority).unwrap(), 3);
+        setsockopt(s, super::IpFreebind, &true).unwrap();
+        assert!(getsockopt(s, super::IpFreebind).unwrap());
+        close(s).unwrap();
+    }
 }
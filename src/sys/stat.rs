@@ -1,11 +1,18 @@
 pub use libc::dev_t;
 pub use libc::stat as FileStat;
 
-use {Errno, Result, NixPath};
+use {Errno, Result, NixPath, at_rawfd};
 use fcntl::AtFlags;
 use libc::{self, mode_t};
 use std::mem;
 use std::os::unix::io::RawFd;
+use sys::time::TimeSpec;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub use sys::time::{UTIME_NOW, UTIME_OMIT};
+
+#[cfg(target_os = "linux")]
+pub use self::linux::*;
 
 mod ffi {
     use libc::{c_char, c_int, mode_t, dev_t};
@@ -13,7 +20,12 @@ mod ffi {
 
     extern {
         pub fn mknod(pathname: *const c_char, mode: mode_t, dev: dev_t) -> c_int;
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        pub fn mknodat(dirfd: c_int, pathname: *const c_char, mode: mode_t, dev: dev_t) -> c_int;
         pub fn umask(mask: mode_t) -> mode_t;
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        pub fn utimensat(dirfd: c_int, pathname: *const c_char,
+                         times: *const ::libc::timespec, flags: c_int) -> c_int;
     }
 }
 
@@ -63,6 +75,22 @@ pub fn mknod<P: ?Sized + NixPath>(path: &P, kind: SFlag, perm: Mode, dev: dev_t)
     Errno::result(res).map(drop)
 }
 
+/// Like `mknod`, for `path` relative to the directory `dirfd`, or to the
+/// current working directory if it is `None`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/mknodat.2.html)
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+pub fn mknodat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, kind: SFlag, perm: Mode,
+                                    dev: dev_t) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe {
+            ffi::mknodat(at_rawfd(dirfd), cstr.as_ptr(), kind.bits | perm.bits() as mode_t, dev)
+        }
+    }));
+
+    Errno::result(res).map(drop)
+}
+
 #[cfg(target_os = "linux")]
 pub fn major(dev: dev_t) -> u64 {
     ((dev >> 32) & 0xfffff000) |
@@ -75,6 +103,57 @@ pub fn minor(dev: dev_t) -> u64 {
     ((dev      ) & 0x000000ff)
 }
 
+#[cfg(target_os = "android")]
+pub fn major(dev: dev_t) -> u64 {
+    let dev = dev as u64;
+    ((dev >> 32) & 0xfffff000) |
+    ((dev >>  8) & 0x00000fff)
+}
+
+#[cfg(target_os = "android")]
+pub fn minor(dev: dev_t) -> u64 {
+    let dev = dev as u64;
+    ((dev >> 12) & 0xffffff00) |
+    ((dev      ) & 0x000000ff)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn major(dev: dev_t) -> u64 {
+    ((dev as u32 as u64) >> 24) & 0xff
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn minor(dev: dev_t) -> u64 {
+    (dev as u32 as u64) & 0x00ffffff
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd"))]
+pub fn major(dev: dev_t) -> u64 {
+    ((dev as u32 as u64) >> 8) & 0xff
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn minor(dev: dev_t) -> u64 {
+    (dev as u32 as u64) & 0xffff00ff
+}
+
+#[cfg(target_os = "openbsd")]
+pub fn minor(dev: dev_t) -> u64 {
+    let dev = dev as u32 as u64;
+    (dev & 0x000000ff) | ((dev & 0xffff0000) >> 8)
+}
+
+#[cfg(target_os = "netbsd")]
+pub fn major(dev: dev_t) -> u64 {
+    ((dev as u64) & 0x000fff00) >> 8
+}
+
+#[cfg(target_os = "netbsd")]
+pub fn minor(dev: dev_t) -> u64 {
+    let dev = dev as u64;
+    (dev & 0x000000ff) | ((dev & 0xfff00000) >> 12)
+}
+
 #[cfg(target_os = "linux")]
 pub fn makedev(major: u64, minor: u64) -> dev_t {
     ((major & 0xfffff000) << 32) |
@@ -83,6 +162,112 @@ pub fn makedev(major: u64, minor: u64) -> dev_t {
     ((minor & 0x000000ff)      )
 }
 
+/// The type of a file, as given by `FileStatExt::file_type`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FileType {
+    Regular,
+    Dir,
+    Symlink,
+    /// A character device, with the major and minor number of the device
+    /// it stands for.
+    CharDev(u64, u64),
+    /// A block device, with the major and minor number of the device it
+    /// stands for.
+    BlockDev(u64, u64),
+    Fifo,
+    Socket,
+    /// A type this enum doesn't know, like the whiteouts of the BSDs, with
+    /// its `S_IFMT` bits.
+    Other(mode_t),
+}
+
+/// Typed accessors for the fields of a `FileStat`, which have different
+/// names and types on different platforms.
+///
+/// # Example
+///
+/// ```
+/// use nix::sys::stat::{stat, FileStatExt, FileType};
+///
+/// let st = stat("/").unwrap();
+/// assert_eq!(st.file_type(), FileType::Dir);
+/// println!("{:?} modified at {}", st.permissions(), st.mtime().tv_sec());
+/// ```
+pub trait FileStatExt {
+    /// The type of the file.
+    fn file_type(&self) -> FileType;
+
+    /// The permission bits, including the set-user-ID, set-group-ID and
+    /// sticky bits.
+    fn permissions(&self) -> Mode;
+
+    /// The time of the last access.
+    fn atime(&self) -> TimeSpec;
+
+    /// The time of the last modification of the contents.
+    fn mtime(&self) -> TimeSpec;
+
+    /// The time of the last status change.
+    fn ctime(&self) -> TimeSpec;
+
+    /// The major and minor number of the device the file is on.
+    fn dev(&self) -> (u64, u64);
+}
+
+fn stat_time(sec: libc::time_t, nsec: libc::c_long) -> TimeSpec {
+    TimeSpec::from(libc::timespec { tv_sec: sec, tv_nsec: nsec })
+}
+
+// The nanoseconds of the access, modification and status change time
+#[cfg(not(target_os = "netbsd"))]
+fn stat_nsecs(st: &FileStat) -> [libc::c_long; 3] {
+    [st.st_atime_nsec as libc::c_long,
+     st.st_mtime_nsec as libc::c_long,
+     st.st_ctime_nsec as libc::c_long]
+}
+
+#[cfg(target_os = "netbsd")]
+fn stat_nsecs(st: &FileStat) -> [libc::c_long; 3] {
+    [st.st_atimensec as libc::c_long,
+     st.st_mtimensec as libc::c_long,
+     st.st_ctimensec as libc::c_long]
+}
+
+impl FileStatExt for FileStat {
+    fn file_type(&self) -> FileType {
+        match self.st_mode as mode_t & S_IFMT.bits() {
+            libc::S_IFREG => FileType::Regular,
+            libc::S_IFDIR => FileType::Dir,
+            libc::S_IFLNK => FileType::Symlink,
+            libc::S_IFCHR => FileType::CharDev(major(self.st_rdev), minor(self.st_rdev)),
+            libc::S_IFBLK => FileType::BlockDev(major(self.st_rdev), minor(self.st_rdev)),
+            libc::S_IFIFO => FileType::Fifo,
+            libc::S_IFSOCK => FileType::Socket,
+            other => FileType::Other(other),
+        }
+    }
+
+    fn permissions(&self) -> Mode {
+        Mode::from_bits_truncate(self.st_mode as mode_t)
+    }
+
+    fn atime(&self) -> TimeSpec {
+        stat_time(self.st_atime as libc::time_t, stat_nsecs(self)[0])
+    }
+
+    fn mtime(&self) -> TimeSpec {
+        stat_time(self.st_mtime as libc::time_t, stat_nsecs(self)[1])
+    }
+
+    fn ctime(&self) -> TimeSpec {
+        stat_time(self.st_ctime as libc::time_t, stat_nsecs(self)[2])
+    }
+
+    fn dev(&self) -> (u64, u64) {
+        (major(self.st_dev), minor(self.st_dev))
+    }
+}
+
 pub fn umask(mode: Mode) -> Mode {
     let prev = unsafe { ffi::umask(mode.bits() as mode_t) };
     Mode::from_bits(prev).expect("[BUG] umask returned invalid Mode")
@@ -134,3 +319,317 @@ pub fn fstatat<P: ?Sized + NixPath>(dirfd: RawFd, pathname: &P, f: AtFlags) -> R
     Ok(dst)
 }
 
+/// Change the permissions of `path`, relative to the directory `dirfd` or
+/// the current working directory if it is `None`.  Linux doesn't support
+/// `AT_SYMLINK_NOFOLLOW` here and fails with `ENOTSUP` for it.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/fchmodat.2.html)
+pub fn fchmodat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, mode: Mode, flags: AtFlags) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::fchmodat(at_rawfd(dirfd), cstr.as_ptr(), mode.bits() as mode_t, flags.bits()) }
+    }));
+
+    Errno::result(res).map(drop)
+}
+
+/// Set the access and modification times of `path`, relative to the
+/// directory `dirfd` or the current working directory if it is `None`, with
+/// nanosecond precision.  Either time can be `UTIME_NOW` or `UTIME_OMIT`.
+/// With `AT_SYMLINK_NOFOLLOW`, the times of a symbolic link are changed
+/// instead of its target's.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/utimensat.2.html)
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub fn utimensat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, atime: &TimeSpec,
+                                      mtime: &TimeSpec, flags: AtFlags) -> Result<()> {
+    let times = [*atime.as_ref(), *mtime.as_ref()];
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { ffi::utimensat(at_rawfd(dirfd), cstr.as_ptr(), times.as_ptr(), flags.bits()) }
+    }));
+
+    Errno::result(res).map(drop)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use libc::{c_long, c_uint, dev_t, gid_t, mode_t, time_t, timespec, uid_t};
+    use std::mem;
+    use std::os::unix::io::RawFd;
+    use {Errno, Error, Result, NixPath, at_rawfd, features};
+    use fcntl::AtFlags;
+    use sys::syscall::{syscall, STATX};
+    use sys::time::TimeSpec;
+    use super::{fstatat, major, minor, makedev, FileStat, Mode, SFlag, S_IFMT};
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug)]
+    struct statx_timestamp {
+        tv_sec: i64,
+        tv_nsec: u32,
+        __reserved: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug)]
+    struct statx {
+        stx_mask: u32,
+        stx_blksize: u32,
+        stx_attributes: u64,
+        stx_nlink: u32,
+        stx_uid: u32,
+        stx_gid: u32,
+        stx_mode: u16,
+        __spare0: u16,
+        stx_ino: u64,
+        stx_size: u64,
+        stx_blocks: u64,
+        stx_attributes_mask: u64,
+        stx_atime: statx_timestamp,
+        stx_btime: statx_timestamp,
+        stx_ctime: statx_timestamp,
+        stx_mtime: statx_timestamp,
+        stx_rdev_major: u32,
+        stx_rdev_minor: u32,
+        stx_dev_major: u32,
+        stx_dev_minor: u32,
+        stx_mnt_id: u64,
+        __spare2: [u64; 13],
+    }
+
+    bitflags!(
+        /// The fields `statx` is asked for, and the ones it filled in.
+        pub flags StatxMask: c_uint {
+            const STATX_TYPE        = 0x0001,
+            const STATX_MODE        = 0x0002,
+            const STATX_NLINK       = 0x0004,
+            const STATX_UID         = 0x0008,
+            const STATX_GID         = 0x0010,
+            const STATX_ATIME       = 0x0020,
+            const STATX_MTIME       = 0x0040,
+            const STATX_CTIME       = 0x0080,
+            const STATX_INO         = 0x0100,
+            const STATX_SIZE        = 0x0200,
+            const STATX_BLOCKS      = 0x0400,
+            /// Everything `stat` returns.
+            const STATX_BASIC_STATS = 0x07ff,
+            /// The creation time.
+            const STATX_BTIME       = 0x0800,
+            /// The mount id, since Linux 5.8.
+            const STATX_MNT_ID      = 0x1000,
+        }
+    );
+
+    bitflags!(
+        /// File attributes, as set with `chattr`.
+        pub flags StatxAttributes: u64 {
+            const STATX_ATTR_COMPRESSED = 0x0000_0004,
+            const STATX_ATTR_IMMUTABLE  = 0x0000_0010,
+            const STATX_ATTR_APPEND     = 0x0000_0020,
+            const STATX_ATTR_NODUMP     = 0x0000_0040,
+            const STATX_ATTR_ENCRYPTED  = 0x0000_0800,
+            const STATX_ATTR_AUTOMOUNT  = 0x0000_1000,
+            const STATX_ATTR_MOUNT_ROOT = 0x0000_2000,
+            const STATX_ATTR_VERITY     = 0x0010_0000,
+            const STATX_ATTR_DAX        = 0x0020_0000,
+        }
+    );
+
+    /// The result of `statx`.  Fields the file system didn't fill in are
+    /// `None`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Statx(statx);
+
+    fn to_timestamp(sec: time_t, nsec: c_long) -> statx_timestamp {
+        statx_timestamp { tv_sec: sec as i64, tv_nsec: nsec as u32, __reserved: 0 }
+    }
+
+    impl Statx {
+        fn from_stat(st: &FileStat) -> Statx {
+            let mut stx: statx = unsafe { mem::zeroed() };
+            stx.stx_mask = STATX_BASIC_STATS.bits();
+            stx.stx_blksize = st.st_blksize as u32;
+            stx.stx_nlink = st.st_nlink as u32;
+            stx.stx_uid = st.st_uid;
+            stx.stx_gid = st.st_gid;
+            stx.stx_mode = st.st_mode as u16;
+            stx.stx_ino = st.st_ino as u64;
+            stx.stx_size = st.st_size as u64;
+            stx.stx_blocks = st.st_blocks as u64;
+            stx.stx_atime = to_timestamp(st.st_atime, st.st_atime_nsec as c_long);
+            stx.stx_ctime = to_timestamp(st.st_ctime, st.st_ctime_nsec as c_long);
+            stx.stx_mtime = to_timestamp(st.st_mtime, st.st_mtime_nsec as c_long);
+            stx.stx_rdev_major = major(st.st_rdev) as u32;
+            stx.stx_rdev_minor = minor(st.st_rdev) as u32;
+            stx.stx_dev_major = major(st.st_dev) as u32;
+            stx.stx_dev_minor = minor(st.st_dev) as u32;
+            Statx(stx)
+        }
+
+        fn field<T>(&self, mask: StatxMask, value: T) -> Option<T> {
+            if self.mask().contains(mask) { Some(value) } else { None }
+        }
+
+        fn time(&self, mask: StatxMask, ts: &statx_timestamp) -> Option<TimeSpec> {
+            self.field(mask, TimeSpec::from(timespec {
+                tv_sec: ts.tv_sec as time_t,
+                tv_nsec: ts.tv_nsec as c_long,
+            }))
+        }
+
+        /// The fields that were filled in.  These may be more than were
+        /// asked for.
+        pub fn mask(&self) -> StatxMask {
+            StatxMask::from_bits_truncate(self.0.stx_mask)
+        }
+
+        /// The preferred block size for I/O.
+        pub fn blksize(&self) -> u32 {
+            self.0.stx_blksize
+        }
+
+        /// The attributes set on the file.
+        pub fn attributes(&self) -> StatxAttributes {
+            StatxAttributes::from_bits_truncate(self.0.stx_attributes & self.0.stx_attributes_mask)
+        }
+
+        /// The attributes the file system supports, so the ones missing from
+        /// `attributes()` are known to be unset.
+        pub fn supported_attributes(&self) -> StatxAttributes {
+            StatxAttributes::from_bits_truncate(self.0.stx_attributes_mask)
+        }
+
+        /// The file type, one of the `S_IF*` flags.
+        pub fn file_type(&self) -> Option<SFlag> {
+            self.field(STATX_TYPE, SFlag::from_bits_truncate(self.0.stx_mode as mode_t & S_IFMT.bits()))
+        }
+
+        /// The permission bits.
+        pub fn mode(&self) -> Option<Mode> {
+            self.field(STATX_MODE, Mode::from_bits_truncate(self.0.stx_mode as mode_t))
+        }
+
+        pub fn nlink(&self) -> Option<u32> {
+            self.field(STATX_NLINK, self.0.stx_nlink)
+        }
+
+        pub fn uid(&self) -> Option<uid_t> {
+            self.field(STATX_UID, self.0.stx_uid as uid_t)
+        }
+
+        pub fn gid(&self) -> Option<gid_t> {
+            self.field(STATX_GID, self.0.stx_gid as gid_t)
+        }
+
+        pub fn ino(&self) -> Option<u64> {
+            self.field(STATX_INO, self.0.stx_ino)
+        }
+
+        pub fn size(&self) -> Option<u64> {
+            self.field(STATX_SIZE, self.0.stx_size)
+        }
+
+        /// The number of 512 byte blocks allocated.
+        pub fn blocks(&self) -> Option<u64> {
+            self.field(STATX_BLOCKS, self.0.stx_blocks)
+        }
+
+        pub fn atime(&self) -> Option<TimeSpec> {
+            self.time(STATX_ATIME, &self.0.stx_atime)
+        }
+
+        /// The creation time.
+        pub fn btime(&self) -> Option<TimeSpec> {
+            self.time(STATX_BTIME, &self.0.stx_btime)
+        }
+
+        pub fn ctime(&self) -> Option<TimeSpec> {
+            self.time(STATX_CTIME, &self.0.stx_ctime)
+        }
+
+        pub fn mtime(&self) -> Option<TimeSpec> {
+            self.time(STATX_MTIME, &self.0.stx_mtime)
+        }
+
+        /// The device the file is on.
+        pub fn dev(&self) -> dev_t {
+            makedev(self.0.stx_dev_major as u64, self.0.stx_dev_minor as u64)
+        }
+
+        /// The device a character or block device file stands for.
+        pub fn rdev(&self) -> dev_t {
+            makedev(self.0.stx_rdev_major as u64, self.0.stx_rdev_minor as u64)
+        }
+
+        /// The id of the mount the file is on, as in `/proc/self/mountinfo`.
+        pub fn mnt_id(&self) -> Option<u64> {
+            self.field(STATX_MNT_ID, self.0.stx_mnt_id)
+        }
+    }
+
+    /// Get the fields of `mask` about `path`, relative to the directory
+    /// `dirfd` or the current working directory if it is `None`.  The file
+    /// system may fill in fewer or more fields than asked for.
+    ///
+    /// Before Linux 4.11 this falls back to `fstatat`, which only provides
+    /// `STATX_BASIC_STATS` and no attributes.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man2/statx.2.html)
+    pub fn statx<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, flags: AtFlags,
+                                      mask: StatxMask) -> Result<Statx> {
+        if !features::statx_available() {
+            return statx_fallback(dirfd, path, flags);
+        }
+
+        let mut dst: statx = unsafe { mem::uninitialized() };
+        let res = try!(path.with_nix_path(|cstr| unsafe {
+            syscall(STATX, at_rawfd(dirfd), cstr.as_ptr(), flags.bits(), mask.bits(),
+                    &mut dst as *mut statx)
+        }));
+
+        match Errno::result(res) {
+            Ok(_) => Ok(Statx(dst)),
+            // Seccomp filters that predate statx reject it with ENOSYS, or
+            // EPERM in older container runtimes.
+            Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EPERM)) => {
+                statx_fallback(dirfd, path, flags)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn statx_fallback<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, flags: AtFlags)
+                                           -> Result<Statx> {
+        let st = try!(fstatat(at_rawfd(dirfd), path, flags));
+        Ok(Statx::from_stat(&st))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use sys::stat::fstat;
+        use std::fs::File;
+        use std::mem;
+        use std::os::unix::io::AsRawFd;
+
+        #[test]
+        fn test_statx_size() {
+            assert_eq!(mem::size_of::<super::statx>(), 256);
+        }
+
+        #[test]
+        fn test_from_stat() {
+            let file = File::open("/").unwrap();
+            let st = fstat(file.as_raw_fd()).unwrap();
+            let stx = Statx::from_stat(&st);
+
+            assert_eq!(stx.mask(), STATX_BASIC_STATS);
+            assert_eq!(stx.ino(), Some(st.st_ino as u64));
+            assert_eq!(stx.dev(), st.st_dev);
+            assert_eq!(stx.file_type(), Some(::sys::stat::S_IFDIR));
+            assert_eq!(stx.mtime().unwrap().tv_sec(), st.st_mtime);
+            assert_eq!(stx.btime(), None);
+            assert_eq!(stx.mnt_id(), None);
+            assert!(stx.supported_attributes().is_empty());
+        }
+    }
+}
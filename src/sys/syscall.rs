@@ -12,6 +12,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 155;
     pub static MEMFD_CREATE: Syscall = 319;
+    pub static RENAMEAT2: Syscall = 316;
+    pub static STATX: Syscall = 332;
 }
 
 #[cfg(target_arch = "x86")]
@@ -22,6 +24,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 217;
     pub static MEMFD_CREATE: Syscall = 356;
+    pub static RENAMEAT2: Syscall = 353;
+    pub static STATX: Syscall = 383;
 }
 
 #[cfg(target_arch = "aarch64")]
@@ -32,6 +36,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 41;
     pub static MEMFD_CREATE: Syscall = 279;
+    pub static RENAMEAT2: Syscall = 276;
+    pub static STATX: Syscall = 291;
 }
 
 #[cfg(target_arch = "arm")]
@@ -42,6 +48,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 218;
     pub static MEMFD_CREATE: Syscall = 385;
+    pub static RENAMEAT2: Syscall = 382;
+    pub static STATX: Syscall = 397;
 }
 
 #[cfg(target_arch = "mips")]
@@ -52,6 +60,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 216;
     pub static MEMFD_CREATE: Syscall = 354;
+    pub static RENAMEAT2: Syscall = 4351;
+    pub static STATX: Syscall = 4366;
 }
 
 #[cfg(target_arch = "powerpc")]
@@ -62,6 +72,8 @@ mod arch {
 
     pub static SYSPIVOTROOT: Syscall = 203;
     pub static MEMFD_CREATE: Syscall = 360;
+    pub static RENAMEAT2: Syscall = 357;
+    pub static STATX: Syscall = 383;
 }
 
 extern {
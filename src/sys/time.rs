@@ -178,6 +178,26 @@ impl TimeSpec {
     }
 }
 
+impl From<timespec> for TimeSpec {
+    fn from(ts: timespec) -> TimeSpec {
+        TimeSpec(ts)
+    }
+}
+
+/// Passed to `utimensat` to set a timestamp to the current time.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const UTIME_NOW: TimeSpec = TimeSpec(timespec { tv_sec: 0, tv_nsec: (1 << 30) - 1 });
+/// Passed to `utimensat` to leave a timestamp unchanged.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const UTIME_OMIT: TimeSpec = TimeSpec(timespec { tv_sec: 0, tv_nsec: (1 << 30) - 2 });
+
+/// Passed to `utimensat` to set a timestamp to the current time.
+#[cfg(target_os = "freebsd")]
+pub const UTIME_NOW: TimeSpec = TimeSpec(timespec { tv_sec: 0, tv_nsec: -1 });
+/// Passed to `utimensat` to leave a timestamp unchanged.
+#[cfg(target_os = "freebsd")]
+pub const UTIME_OMIT: TimeSpec = TimeSpec(timespec { tv_sec: 0, tv_nsec: -2 });
+
 impl ops::Neg for TimeSpec {
     type Output = TimeSpec;
 
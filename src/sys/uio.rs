@@ -3,6 +3,8 @@
 
 use {Errno, Result};
 use libc::{self, c_int, c_void, size_t, off_t};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc::{c_ulong, pid_t};
 use std::marker::PhantomData;
 use std::os::unix::io::RawFd;
 
@@ -56,6 +58,73 @@ pub fn pread(fd: RawFd, buf: &mut [u8], offset: off_t) -> Result<usize>{
     Errno::result(res).map(|r| r as usize)
 }
 
+/// A slice of memory in a remote process, starting at virtual address `base`
+/// and consisting of `len` bytes.
+///
+/// This is the same underlying C structure as `IoVec`, except that it refers
+/// to memory in some other process, and thus is not represented in Rust by an
+/// actual slice as `IoVec` is.  It is used with `process_vm_readv` and
+/// `process_vm_writev`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RemoteIoVec {
+    /// The starting address of this slice (`iov_base`).
+    pub base: usize,
+    /// The number of bytes in this slice (`iov_len`).
+    pub len: usize,
+}
+
+/// Write data directly to another process's virtual memory
+/// (see [`process_vm_writev`(2)](http://man7.org/linux/man-pages/man2/process_vm_writev.2.html)).
+///
+/// `local_iov` is a list of `IoVec`s containing the data to be written,
+/// and `remote_iov` is a list of `RemoteIoVec`s identifying where the
+/// data should be written in the target process.  On success, returns the
+/// number of bytes written, which will always be a whole number of
+/// `remote_iov` chunks.  A short count means that the transfer stopped at the
+/// first remote chunk that could not be written.
+///
+/// This requires the same permissions as debugging the process using
+/// `ptrace`: you must either be a privileged process (with `CAP_SYS_PTRACE`),
+/// or you must be running as the same user as the target process and the OS
+/// must have unprivileged debugging enabled.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn process_vm_writev(pid: pid_t, local_iov: &[IoVec<&[u8]>], remote_iov: &[RemoteIoVec]) -> Result<usize> {
+    let res = unsafe {
+        libc::process_vm_writev(pid,
+                                local_iov.as_ptr() as *const libc::iovec, local_iov.len() as c_ulong,
+                                remote_iov.as_ptr() as *const libc::iovec, remote_iov.len() as c_ulong, 0)
+    };
+
+    Errno::result(res).map(|r| r as usize)
+}
+
+/// Read data directly from another process's virtual memory
+/// (see [`process_vm_readv`(2)](http://man7.org/linux/man-pages/man2/process_vm_readv.2.html)).
+///
+/// `local_iov` is a list of `IoVec`s containing the buffer to copy
+/// data into, and `remote_iov` is a list of `RemoteIoVec`s identifying
+/// where the source data is in the target process.  On success,
+/// returns the number of bytes read, which will always be a whole
+/// number of `remote_iov` chunks.  A short count means that the transfer
+/// stopped at the first remote chunk that could not be read.
+///
+/// This requires the same permissions as debugging the process using
+/// `ptrace`: you must either be a privileged process (with `CAP_SYS_PTRACE`),
+/// or you must be running as the same user as the target process and the OS
+/// must have unprivileged debugging enabled.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn process_vm_readv(pid: pid_t, local_iov: &[IoVec<&mut [u8]>], remote_iov: &[RemoteIoVec]) -> Result<usize> {
+    let res = unsafe {
+        libc::process_vm_readv(pid,
+                               local_iov.as_ptr() as *const libc::iovec, local_iov.len() as c_ulong,
+                               remote_iov.as_ptr() as *const libc::iovec, remote_iov.len() as c_ulong, 0)
+    };
+
+    Errno::result(res).map(|r| r as usize)
+}
+
 #[repr(C)]
 pub struct IoVec<T>(libc::iovec, PhantomData<T>); 
 
@@ -95,3 +164,10 @@ pub fn test_size_of_io_vec() {
     use nixtest;
     nixtest::assert_size_of::<IoVec<&[u8]>>("iovec");
 }
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_size_of_remote_io_vec() {
+    use nixtest;
+    nixtest::assert_size_of::<RemoteIoVec>("iovec");
+}
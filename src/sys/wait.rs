@@ -1,14 +1,37 @@
 use libc::{self, pid_t, c_int};
 use {Errno, Result};
+use std::mem;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::fmt;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use Error;
 
 use sys::signal::Signal;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use sys::signal::{ChildCode, SigCode, SigInfo};
 
 mod ffi {
     use libc::{pid_t, c_int};
 
     extern {
         pub fn waitpid(pid: pid_t, status: *mut c_int, options: c_int) -> pid_t;
+        pub fn wait4(pid: pid_t, status: *mut c_int, options: c_int,
+                     rusage: *mut ::libc::rusage) -> pid_t;
     }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    extern {
+        pub fn waitid(idtype: c_int, id: ::libc::id_t, infop: *mut ::libc::siginfo_t,
+                      options: c_int) -> c_int;
+    }
+
+    // Values of idtype_t from <sys/wait.h>
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const P_ALL: c_int = 0;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const P_PID: c_int = 1;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const P_PGID: c_int = 2;
 }
 
 #[cfg(not(any(target_os = "linux",
@@ -27,6 +50,7 @@ libc_bitflags!(
         WNOHANG,
         WUNTRACED,
         WEXITED,
+        WSTOPPED, // Same as WUNTRACED, the name used by waitid
         WCONTINUED,
         WNOWAIT, // Don't reap, just poll status.
         __WNOTHREAD, // Don't wait on children of other threads in this group
@@ -35,10 +59,6 @@ libc_bitflags!(
     }
 );
 
-#[cfg(any(target_os = "linux",
-          target_os = "android"))]
-const WSTOPPED: WaitPidFlag = WUNTRACED;
-
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub enum WaitStatus {
     Exited(pid_t, i8),
@@ -46,6 +66,10 @@ pub enum WaitStatus {
     Stopped(pid_t, Signal),
     #[cfg(any(target_os = "linux", target_os = "android"))]
     PtraceEvent(pid_t, Signal, c_int),
+    /// A syscall-stop of a tracee with `PTRACE_O_TRACESYSGOOD` set, which is
+    /// reported as a stop by `SIGTRAP | 0x80`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    PtraceSyscall(pid_t),
     Continued(pid_t),
     StillAlive
 }
@@ -54,7 +78,7 @@ pub enum WaitStatus {
           target_os = "android"))]
 mod status {
     use sys::signal::Signal;
-    use libc::c_int;
+    use libc::{self, c_int};
 
     pub fn exited(status: i32) -> bool {
         (status & 0x7F) == 0
@@ -88,6 +112,11 @@ mod status {
         (status >> 16) as c_int
     }
 
+    pub fn syscall_stop(status: i32) -> bool {
+        // SIGTRAP | 0x80 is only reported with PTRACE_O_TRACESYSGOOD
+        ((status & 0xFF00) >> 8) == (libc::SIGTRAP | 0x80)
+    }
+
     pub fn continued(status: i32) -> bool {
         status == 0xFFFF
     }
@@ -195,7 +224,9 @@ fn decode(pid : pid_t, status: i32) -> WaitStatus {
             if #[cfg(any(target_os = "linux", target_os = "android"))] {
                 fn decode_stopped(pid: pid_t, status: i32) -> WaitStatus {
                     let status_additional = status::stop_additional(status);
-                    if status_additional == 0 {
+                    if status::syscall_stop(status) {
+                        WaitStatus::PtraceSyscall(pid)
+                    } else if status_additional == 0 {
                         WaitStatus::Stopped(pid, status::stop_signal(status))
                     } else {
                         WaitStatus::PtraceEvent(pid, status::stop_signal(status), status::stop_additional(status))
@@ -235,3 +266,171 @@ pub fn waitpid(pid: pid_t, options: Option<WaitPidFlag>) -> Result<WaitStatus> {
 pub fn wait() -> Result<WaitStatus> {
     waitpid(-1, None)
 }
+
+/// Like `waitpid`, but also returns the resource usage of the child (see
+/// [wait4(2)](http://man7.org/linux/man-pages/man2/wait4.2.html)).
+///
+/// The resource usage is only meaningful when the child has terminated; it
+/// covers the child and all of its waited-for descendants.
+pub fn wait4(pid: pid_t, options: Option<WaitPidFlag>) -> Result<(WaitStatus, libc::rusage)> {
+    use self::WaitStatus::*;
+
+    let mut status: i32 = 0;
+    let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+
+    let option_bits = match options {
+        Some(bits) => bits.bits(),
+        None => 0
+    };
+
+    let res = unsafe {
+        ffi::wait4(pid, &mut status as *mut c_int, option_bits, &mut rusage as *mut libc::rusage)
+    };
+
+    Ok(match try!(Errno::result(res)) {
+        0 => (StillAlive, rusage),
+        res => (decode(res, status), rusage),
+    })
+}
+
+/// Like `wait4(-1, options)`: waits for any child and also returns its
+/// resource usage.
+pub fn wait3(options: Option<WaitPidFlag>) -> Result<(WaitStatus, libc::rusage)> {
+    wait4(-1, options)
+}
+
+/// The children to wait for with `waitid`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Id {
+    /// Any child process.
+    All,
+    /// The child process with the given pid.
+    Pid(pid_t),
+    /// Any child process in the given process group.
+    Pgid(pid_t),
+}
+
+/// The result of a successful `waitid`: the `siginfo_t` filled in by the
+/// kernel, which carries more information than the wait status of `waitpid`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy)]
+pub struct WaitIdInfo {
+    siginfo: libc::siginfo_t,
+    code: ChildCode,
+    pid: pid_t,
+    uid: libc::uid_t,
+    status: c_int,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl WaitIdInfo {
+    fn from_siginfo(siginfo: libc::siginfo_t) -> Result<WaitIdInfo> {
+        match SigInfo::from(&siginfo).code() {
+            SigCode::Child { code, pid, uid, status } => Ok(WaitIdInfo {
+                siginfo: siginfo,
+                code: code,
+                pid: pid,
+                uid: uid,
+                status: status,
+            }),
+            _ => Err(Error::Sys(Errno::EINVAL)),
+        }
+    }
+
+    /// The pid of the child whose state changed (`si_pid`).
+    pub fn pid(&self) -> pid_t {
+        self.pid
+    }
+
+    /// The real user id of the child (`si_uid`).
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// The kind of state change (`si_code`).
+    pub fn code(&self) -> ChildCode {
+        self.code
+    }
+
+    /// The exit status or signal number of the child, depending on `code`
+    /// (`si_status`).
+    pub fn status_value(&self) -> c_int {
+        self.status
+    }
+
+    /// Decodes the state change into a `WaitStatus`.  Fails with `EINVAL` if
+    /// the signal number in `status_value` is not a valid `Signal`.
+    pub fn status(&self) -> Result<WaitStatus> {
+        let pid = self.pid;
+        let status = self.status;
+        Ok(match self.code {
+            ChildCode::Exited => WaitStatus::Exited(pid, status as i8),
+            ChildCode::Killed => WaitStatus::Signaled(pid, try!(Signal::from_c_int(status)), false),
+            ChildCode::Dumped => WaitStatus::Signaled(pid, try!(Signal::from_c_int(status)), true),
+            ChildCode::Trapped if status == libc::SIGTRAP | 0x80 => WaitStatus::PtraceSyscall(pid),
+            ChildCode::Trapped | ChildCode::Stopped => {
+                // For ptrace event stops the event number is in the high byte
+                let event = status >> 8;
+                let signal = try!(Signal::from_c_int(status & 0xff));
+                if event == 0 {
+                    WaitStatus::Stopped(pid, signal)
+                } else {
+                    WaitStatus::PtraceEvent(pid, signal, event)
+                }
+            },
+            ChildCode::Continued => WaitStatus::Continued(pid),
+        })
+    }
+
+    /// The raw `siginfo_t` filled in by `waitid`.
+    pub fn siginfo(&self) -> &libc::siginfo_t {
+        &self.siginfo
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl fmt::Debug for WaitIdInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WaitIdInfo")
+            .field("pid", &self.pid)
+            .field("uid", &self.uid)
+            .field("code", &self.code)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+/// Waits for a state change in the children selected by `id` (see
+/// [waitid(2)](http://man7.org/linux/man-pages/man2/waitid.2.html)).
+///
+/// Unlike `waitpid`, this reports the sender uid and the raw `siginfo_t`, and
+/// with `WNOWAIT` it only peeks at the state change, leaving the child
+/// waitable so that it can be reaped later.  `flags` must include at least one
+/// of `WEXITED`, `WSTOPPED` and `WCONTINUED`, or this fails with `EINVAL`.  If
+/// `WNOHANG` is given and no selected child has changed state yet, `None` is
+/// returned.
+///
+/// `flags` is a `WaitPidFlag`: on Linux its flags are exactly the ones the
+/// kernel accepts for `waitid`, with `WSTOPPED` being the same bit as
+/// `WUNTRACED`, and `__WALL`, `__WCLONE` and `__WNOTHREAD` work with both
+/// calls.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn waitid(id: Id, flags: WaitPidFlag) -> Result<Option<WaitIdInfo>> {
+    let (idtype, idval) = match id {
+        Id::All => (ffi::P_ALL, 0),
+        Id::Pid(pid) => (ffi::P_PID, pid as libc::id_t),
+        Id::Pgid(pgid) => (ffi::P_PGID, pgid as libc::id_t),
+    };
+
+    // The kernel leaves the siginfo zeroed in the WNOHANG case
+    let mut siginfo: libc::siginfo_t = unsafe { mem::zeroed() };
+    let res = unsafe { ffi::waitid(idtype, idval, &mut siginfo, flags.bits()) };
+    try!(Errno::result(res));
+
+    if siginfo.si_signo == 0 {
+        Ok(None)
+    } else {
+        WaitIdInfo::from_siginfo(siginfo).map(Some)
+    }
+}
@@ -0,0 +1,521 @@
+//! Extended attributes, name-value pairs stored with a file beside its
+//! contents.
+//!
+//! Each operation comes in three flavors: one for a path, one for a path
+//! that doesn't follow a final symbolic link (the `l` prefix), and one for an
+//! open file descriptor (the `f` prefix).
+//!
+//! Names carry their namespace as a prefix, like `user.mime_type` or
+//! `security.selinux`.  On FreeBSD, DragonFly and NetBSD, which implement
+//! the `extattr_*` interface instead, only the `user.` and `system.`
+//! namespaces are available.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/xattr.7.html)
+
+use libc::{c_int, c_void, size_t, ssize_t};
+use std::ffi::{CStr, OsString};
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use {Errno, Error, NixPath, Result};
+
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const CREATE: c_int = 1;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const REPLACE: c_int = 2;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const CREATE: c_int = 2;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const REPLACE: c_int = 4;
+
+bitflags!(
+    /// Flags for `setxattr`
+    pub flags XattrFlags: c_int {
+        /// Fail with `EEXIST` if the attribute exists already.
+        const XATTR_CREATE  = CREATE,
+        /// Fail with `ENODATA`, or `ENOATTR` on the BSDs, if the attribute
+        /// doesn't exist yet.
+        const XATTR_REPLACE = REPLACE,
+    }
+);
+
+/// The file an operation applies to.
+#[derive(Clone, Copy)]
+enum Target<'a> {
+    Path(&'a CStr),
+    Link(&'a CStr),
+    Fd(RawFd),
+}
+
+/// Learn the size of a value or name list from `f`, then read it into a
+/// buffer of that size, and start over if it grew in between.
+fn read_buffer<F>(mut f: F) -> Result<Vec<u8>>
+    where F: FnMut(*mut c_void, size_t) -> ssize_t
+{
+    loop {
+        let size = try!(Errno::result(f(ptr::null_mut(), 0))) as usize;
+        // The BSDs silently truncate instead of failing with ERANGE, so one
+        // spare byte tells a complete read from a truncated one.
+        let mut buf = Vec::with_capacity(size + 1);
+        match Errno::result(f(buf.as_mut_ptr() as *mut c_void, size + 1)) {
+            Ok(len) if len as usize <= size => {
+                unsafe { buf.set_len(len as usize) };
+                return Ok(buf);
+            }
+            Ok(_) | Err(Error::Sys(Errno::ERANGE)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use libc::{c_char, c_int, c_void, size_t, ssize_t};
+    use std::ffi::CStr;
+    use {Errno, Result};
+    use super::{read_buffer, Target, XattrFlags};
+
+    extern {
+        fn getxattr(path: *const c_char, name: *const c_char, value: *mut c_void,
+                    size: size_t) -> ssize_t;
+        fn lgetxattr(path: *const c_char, name: *const c_char, value: *mut c_void,
+                     size: size_t) -> ssize_t;
+        fn fgetxattr(fd: c_int, name: *const c_char, value: *mut c_void,
+                     size: size_t) -> ssize_t;
+        fn setxattr(path: *const c_char, name: *const c_char, value: *const c_void,
+                    size: size_t, flags: c_int) -> c_int;
+        fn lsetxattr(path: *const c_char, name: *const c_char, value: *const c_void,
+                     size: size_t, flags: c_int) -> c_int;
+        fn fsetxattr(fd: c_int, name: *const c_char, value: *const c_void,
+                     size: size_t, flags: c_int) -> c_int;
+        fn listxattr(path: *const c_char, list: *mut c_char, size: size_t) -> ssize_t;
+        fn llistxattr(path: *const c_char, list: *mut c_char, size: size_t) -> ssize_t;
+        fn flistxattr(fd: c_int, list: *mut c_char, size: size_t) -> ssize_t;
+        fn removexattr(path: *const c_char, name: *const c_char) -> c_int;
+        fn lremovexattr(path: *const c_char, name: *const c_char) -> c_int;
+        fn fremovexattr(fd: c_int, name: *const c_char) -> c_int;
+    }
+
+    pub fn get(target: Target, name: &CStr) -> Result<Vec<u8>> {
+        let name = name.as_ptr();
+        read_buffer(|buf, size| unsafe {
+            match target {
+                Target::Path(p) => getxattr(p.as_ptr(), name, buf, size),
+                Target::Link(p) => lgetxattr(p.as_ptr(), name, buf, size),
+                Target::Fd(fd) => fgetxattr(fd, name, buf, size),
+            }
+        })
+    }
+
+    pub fn set(target: Target, name: &CStr, value: &[u8], flags: XattrFlags) -> Result<()> {
+        let (name, val, len) = (name.as_ptr(), value.as_ptr() as *const c_void, value.len());
+        let res = unsafe {
+            match target {
+                Target::Path(p) => setxattr(p.as_ptr(), name, val, len, flags.bits()),
+                Target::Link(p) => lsetxattr(p.as_ptr(), name, val, len, flags.bits()),
+                Target::Fd(fd) => fsetxattr(fd, name, val, len, flags.bits()),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+
+    pub fn list(target: Target) -> Result<Vec<u8>> {
+        read_buffer(|buf, size| unsafe {
+            let buf = buf as *mut c_char;
+            match target {
+                Target::Path(p) => listxattr(p.as_ptr(), buf, size),
+                Target::Link(p) => llistxattr(p.as_ptr(), buf, size),
+                Target::Fd(fd) => flistxattr(fd, buf, size),
+            }
+        })
+    }
+
+    pub fn remove(target: Target, name: &CStr) -> Result<()> {
+        let res = unsafe {
+            match target {
+                Target::Path(p) => removexattr(p.as_ptr(), name.as_ptr()),
+                Target::Link(p) => lremovexattr(p.as_ptr(), name.as_ptr()),
+                Target::Fd(fd) => fremovexattr(fd, name.as_ptr()),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod imp {
+    use libc::{c_char, c_int, c_void, size_t, ssize_t, uint32_t};
+    use std::ffi::CStr;
+    use {Errno, Result};
+    use super::{read_buffer, Target, XattrFlags};
+
+    const XATTR_NOFOLLOW: c_int = 1;
+
+    // There's no separate function for symbolic links, they take
+    // `XATTR_NOFOLLOW` instead.  `position` is only used for resource forks.
+    extern {
+        fn getxattr(path: *const c_char, name: *const c_char, value: *mut c_void,
+                    size: size_t, position: uint32_t, options: c_int) -> ssize_t;
+        fn fgetxattr(fd: c_int, name: *const c_char, value: *mut c_void,
+                     size: size_t, position: uint32_t, options: c_int) -> ssize_t;
+        fn setxattr(path: *const c_char, name: *const c_char, value: *const c_void,
+                    size: size_t, position: uint32_t, options: c_int) -> c_int;
+        fn fsetxattr(fd: c_int, name: *const c_char, value: *const c_void,
+                     size: size_t, position: uint32_t, options: c_int) -> c_int;
+        fn listxattr(path: *const c_char, namebuf: *mut c_char, size: size_t,
+                     options: c_int) -> ssize_t;
+        fn flistxattr(fd: c_int, namebuf: *mut c_char, size: size_t,
+                      options: c_int) -> ssize_t;
+        fn removexattr(path: *const c_char, name: *const c_char, options: c_int) -> c_int;
+        fn fremovexattr(fd: c_int, name: *const c_char, options: c_int) -> c_int;
+    }
+
+    pub fn get(target: Target, name: &CStr) -> Result<Vec<u8>> {
+        let name = name.as_ptr();
+        read_buffer(|buf, size| unsafe {
+            match target {
+                Target::Path(p) => getxattr(p.as_ptr(), name, buf, size, 0, 0),
+                Target::Link(p) => getxattr(p.as_ptr(), name, buf, size, 0, XATTR_NOFOLLOW),
+                Target::Fd(fd) => fgetxattr(fd, name, buf, size, 0, 0),
+            }
+        })
+    }
+
+    pub fn set(target: Target, name: &CStr, value: &[u8], flags: XattrFlags) -> Result<()> {
+        let (name, val, len) = (name.as_ptr(), value.as_ptr() as *const c_void, value.len());
+        let res = unsafe {
+            match target {
+                Target::Path(p) => setxattr(p.as_ptr(), name, val, len, 0, flags.bits()),
+                Target::Link(p) => {
+                    setxattr(p.as_ptr(), name, val, len, 0, flags.bits() | XATTR_NOFOLLOW)
+                }
+                Target::Fd(fd) => fsetxattr(fd, name, val, len, 0, flags.bits()),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+
+    pub fn list(target: Target) -> Result<Vec<u8>> {
+        read_buffer(|buf, size| unsafe {
+            let buf = buf as *mut c_char;
+            match target {
+                Target::Path(p) => listxattr(p.as_ptr(), buf, size, 0),
+                Target::Link(p) => listxattr(p.as_ptr(), buf, size, XATTR_NOFOLLOW),
+                Target::Fd(fd) => flistxattr(fd, buf, size, 0),
+            }
+        })
+    }
+
+    pub fn remove(target: Target, name: &CStr) -> Result<()> {
+        let res = unsafe {
+            match target {
+                Target::Path(p) => removexattr(p.as_ptr(), name.as_ptr(), 0),
+                Target::Link(p) => removexattr(p.as_ptr(), name.as_ptr(), XATTR_NOFOLLOW),
+                Target::Fd(fd) => fremovexattr(fd, name.as_ptr(), 0),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd"))]
+mod imp {
+    use libc::{c_char, c_int, c_void, size_t, ssize_t};
+    use std::ffi::CStr;
+    use {Errno, Error, Result};
+    use super::{read_buffer, Target, XattrFlags, XATTR_CREATE, XATTR_REPLACE};
+
+    const EXTATTR_NAMESPACE_USER: c_int = 1;
+    const EXTATTR_NAMESPACE_SYSTEM: c_int = 2;
+
+    // The namespaces and their prefixes in the names of the common interface
+    const NAMESPACES: [(c_int, &'static [u8]); 2] = [
+        (EXTATTR_NAMESPACE_USER, b"user."),
+        (EXTATTR_NAMESPACE_SYSTEM, b"system."),
+    ];
+
+    #[cfg(target_os = "freebsd")]
+    type set_ret_t = ssize_t;
+    #[cfg(not(target_os = "freebsd"))]
+    type set_ret_t = c_int;
+
+    extern {
+        fn extattr_get_file(path: *const c_char, attrnamespace: c_int,
+                            attrname: *const c_char, data: *mut c_void,
+                            nbytes: size_t) -> ssize_t;
+        fn extattr_get_link(path: *const c_char, attrnamespace: c_int,
+                            attrname: *const c_char, data: *mut c_void,
+                            nbytes: size_t) -> ssize_t;
+        fn extattr_get_fd(fd: c_int, attrnamespace: c_int, attrname: *const c_char,
+                          data: *mut c_void, nbytes: size_t) -> ssize_t;
+        fn extattr_set_file(path: *const c_char, attrnamespace: c_int,
+                            attrname: *const c_char, data: *const c_void,
+                            nbytes: size_t) -> set_ret_t;
+        fn extattr_set_link(path: *const c_char, attrnamespace: c_int,
+                            attrname: *const c_char, data: *const c_void,
+                            nbytes: size_t) -> set_ret_t;
+        fn extattr_set_fd(fd: c_int, attrnamespace: c_int, attrname: *const c_char,
+                          data: *const c_void, nbytes: size_t) -> set_ret_t;
+        fn extattr_delete_file(path: *const c_char, attrnamespace: c_int,
+                               attrname: *const c_char) -> c_int;
+        fn extattr_delete_link(path: *const c_char, attrnamespace: c_int,
+                               attrname: *const c_char) -> c_int;
+        fn extattr_delete_fd(fd: c_int, attrnamespace: c_int,
+                             attrname: *const c_char) -> c_int;
+        fn extattr_list_file(path: *const c_char, attrnamespace: c_int,
+                             data: *mut c_void, nbytes: size_t) -> ssize_t;
+        fn extattr_list_link(path: *const c_char, attrnamespace: c_int,
+                             data: *mut c_void, nbytes: size_t) -> ssize_t;
+        fn extattr_list_fd(fd: c_int, attrnamespace: c_int, data: *mut c_void,
+                           nbytes: size_t) -> ssize_t;
+    }
+
+    /// Split `name` into its namespace and the name within it.
+    fn split_name(name: &CStr) -> Result<(c_int, &CStr)> {
+        let bytes = name.to_bytes();
+        for &(namespace, prefix) in NAMESPACES.iter() {
+            if bytes.starts_with(prefix) {
+                // The rest is still terminated by the original NUL
+                let rest = unsafe { CStr::from_ptr(name.as_ptr().offset(prefix.len() as isize)) };
+                return Ok((namespace, rest));
+            }
+        }
+        Err(Error::Sys(::errno::EOPNOTSUPP))
+    }
+
+    fn get_raw(target: Target, namespace: c_int, name: &CStr, buf: *mut c_void,
+               size: size_t) -> ssize_t {
+        let name = name.as_ptr();
+        unsafe {
+            match target {
+                Target::Path(p) => extattr_get_file(p.as_ptr(), namespace, name, buf, size),
+                Target::Link(p) => extattr_get_link(p.as_ptr(), namespace, name, buf, size),
+                Target::Fd(fd) => extattr_get_fd(fd, namespace, name, buf, size),
+            }
+        }
+    }
+
+    pub fn get(target: Target, name: &CStr) -> Result<Vec<u8>> {
+        let (namespace, name) = try!(split_name(name));
+        read_buffer(|buf, size| get_raw(target, namespace, name, buf, size))
+    }
+
+    /// `extattr_set_*` has no flags, so `XATTR_CREATE` and `XATTR_REPLACE`
+    /// are checked beforehand, which races with other writers.
+    pub fn set(target: Target, name: &CStr, value: &[u8], flags: XattrFlags) -> Result<()> {
+        let (namespace, name) = try!(split_name(name));
+        if flags.intersects(XATTR_CREATE | XATTR_REPLACE) {
+            let exists = match Errno::result(get_raw(target, namespace, name, ::std::ptr::null_mut(), 0)) {
+                Ok(_) => true,
+                Err(Error::Sys(Errno::ENOATTR)) => false,
+                Err(e) => return Err(e),
+            };
+            if exists && flags.contains(XATTR_CREATE) {
+                return Err(Error::Sys(Errno::EEXIST));
+            }
+            if !exists && flags.contains(XATTR_REPLACE) {
+                return Err(Error::Sys(Errno::ENOATTR));
+            }
+        }
+
+        let (name, val, len) = (name.as_ptr(), value.as_ptr() as *const c_void, value.len());
+        let res = unsafe {
+            match target {
+                Target::Path(p) => extattr_set_file(p.as_ptr(), namespace, name, val, len),
+                Target::Link(p) => extattr_set_link(p.as_ptr(), namespace, name, val, len),
+                Target::Fd(fd) => extattr_set_fd(fd, namespace, name, val, len),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+
+    /// The names of all namespaces, each prefixed and terminated with a NUL
+    /// like on Linux.  Unprivileged users can't list the `system` namespace,
+    /// which is skipped for them.
+    pub fn list(target: Target) -> Result<Vec<u8>> {
+        let mut names = Vec::new();
+        for &(namespace, prefix) in NAMESPACES.iter() {
+            let res = read_buffer(|buf, size| unsafe {
+                match target {
+                    Target::Path(p) => extattr_list_file(p.as_ptr(), namespace, buf, size),
+                    Target::Link(p) => extattr_list_link(p.as_ptr(), namespace, buf, size),
+                    Target::Fd(fd) => extattr_list_fd(fd, namespace, buf, size),
+                }
+            });
+            let raw = match res {
+                Ok(raw) => raw,
+                Err(Error::Sys(Errno::EPERM)) | Err(Error::Sys(Errno::EACCES))
+                    if namespace == EXTATTR_NAMESPACE_SYSTEM => continue,
+                Err(e) => return Err(e),
+            };
+
+            // Each name is preceded by its length in a single byte
+            let mut i = 0;
+            while i < raw.len() {
+                let end = ::std::cmp::min(i + 1 + raw[i] as usize, raw.len());
+                names.extend_from_slice(prefix);
+                names.extend_from_slice(&raw[i + 1..end]);
+                names.push(0);
+                i = end;
+            }
+        }
+        Ok(names)
+    }
+
+    pub fn remove(target: Target, name: &CStr) -> Result<()> {
+        let (namespace, name) = try!(split_name(name));
+        let res = unsafe {
+            match target {
+                Target::Path(p) => extattr_delete_file(p.as_ptr(), namespace, name.as_ptr()),
+                Target::Link(p) => extattr_delete_link(p.as_ptr(), namespace, name.as_ptr()),
+                Target::Fd(fd) => extattr_delete_fd(fd, namespace, name.as_ptr()),
+            }
+        };
+        Errno::result(res).map(drop)
+    }
+}
+
+fn with_path<P, T, F>(path: &P, follow: bool, f: F) -> Result<T>
+    where P: ?Sized + NixPath, F: FnOnce(Target) -> Result<T>
+{
+    try!(path.with_nix_path(|p| {
+        f(if follow { Target::Path(p) } else { Target::Link(p) })
+    }))
+}
+
+fn get<N: ?Sized + NixPath>(target: Target, name: &N) -> Result<Vec<u8>> {
+    try!(name.with_nix_path(|n| imp::get(target, n)))
+}
+
+fn set<N: ?Sized + NixPath>(target: Target, name: &N, value: &[u8], flags: XattrFlags) -> Result<()> {
+    try!(name.with_nix_path(|n| imp::set(target, n, value, flags)))
+}
+
+fn list(target: Target) -> Result<XattrNames> {
+    imp::list(target).map(|buf| XattrNames { buf: buf, pos: 0 })
+}
+
+fn remove<N: ?Sized + NixPath>(target: Target, name: &N) -> Result<()> {
+    try!(name.with_nix_path(|n| imp::remove(target, n)))
+}
+
+/// Get the value of the attribute `name` of `path`.
+///
+/// # Example
+///
+/// ```no_run
+/// use nix::sys::xattr;
+///
+/// let label = xattr::getxattr("/etc/passwd", "security.selinux").unwrap();
+/// println!("{}", String::from_utf8_lossy(&label));
+/// ```
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/getxattr.2.html)
+pub fn getxattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N) -> Result<Vec<u8>> {
+    with_path(path, true, |target| get(target, name))
+}
+
+/// Like `getxattr`, but gets the attribute of a symbolic link itself.
+pub fn lgetxattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N) -> Result<Vec<u8>> {
+    with_path(path, false, |target| get(target, name))
+}
+
+/// Like `getxattr`, for an open file.
+pub fn fgetxattr<N: ?Sized + NixPath>(fd: RawFd, name: &N) -> Result<Vec<u8>> {
+    get(Target::Fd(fd), name)
+}
+
+/// Set the attribute `name` of `path` to `value`, creating or replacing it
+/// unless `flags` demand either.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/setxattr.2.html)
+pub fn setxattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N, value: &[u8],
+                                                        flags: XattrFlags) -> Result<()> {
+    with_path(path, true, |target| set(target, name, value, flags))
+}
+
+/// Like `setxattr`, but sets the attribute of a symbolic link itself.
+pub fn lsetxattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N, value: &[u8],
+                                                         flags: XattrFlags) -> Result<()> {
+    with_path(path, false, |target| set(target, name, value, flags))
+}
+
+/// Like `setxattr`, for an open file.
+pub fn fsetxattr<N: ?Sized + NixPath>(fd: RawFd, name: &N, value: &[u8],
+                                      flags: XattrFlags) -> Result<()> {
+    set(Target::Fd(fd), name, value, flags)
+}
+
+/// List the names of the attributes of `path`.  Names of namespaces the
+/// caller has no access to are left out.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/listxattr.2.html)
+pub fn listxattr<P: ?Sized + NixPath>(path: &P) -> Result<XattrNames> {
+    with_path(path, true, list)
+}
+
+/// Like `listxattr`, but lists the attributes of a symbolic link itself.
+pub fn llistxattr<P: ?Sized + NixPath>(path: &P) -> Result<XattrNames> {
+    with_path(path, false, list)
+}
+
+/// Like `listxattr`, for an open file.
+pub fn flistxattr(fd: RawFd) -> Result<XattrNames> {
+    list(Target::Fd(fd))
+}
+
+/// Remove the attribute `name` of `path`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/removexattr.2.html)
+pub fn removexattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N) -> Result<()> {
+    with_path(path, true, |target| remove(target, name))
+}
+
+/// Like `removexattr`, but removes the attribute of a symbolic link itself.
+pub fn lremovexattr<P: ?Sized + NixPath, N: ?Sized + NixPath>(path: &P, name: &N) -> Result<()> {
+    with_path(path, false, |target| remove(target, name))
+}
+
+/// Like `removexattr`, for an open file.
+pub fn fremovexattr<N: ?Sized + NixPath>(fd: RawFd, name: &N) -> Result<()> {
+    remove(Target::Fd(fd), name)
+}
+
+/// An iterator over the attribute names returned by `listxattr`.
+#[derive(Clone, Debug)]
+pub struct XattrNames {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Iterator for XattrNames {
+    type Item = OsString;
+
+    fn next(&mut self) -> Option<OsString> {
+        let rest = &self.buf[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+        let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        self.pos += ::std::cmp::min(len + 1, rest.len());
+        Some(OsString::from_vec(rest[..len].to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_xattr_names() {
+        let names = XattrNames { buf: b"user.a\0security.selinux\0\0user.b".to_vec(), pos: 0 };
+        let names: Vec<OsString> = names.collect();
+        assert_eq!(names, vec![OsString::from("user.a"), OsString::from("security.selinux"),
+                               OsString::from(""), OsString::from("user.b")]);
+
+        let mut empty = XattrNames { buf: Vec::new(), pos: 0 };
+        assert_eq!(empty.next(), None);
+    }
+}
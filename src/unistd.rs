@@ -1,7 +1,7 @@
 //! Safe wrappers around functions found in libc "unistd.h" header
 
-use {Errno, Error, Result, NixPath};
-use fcntl::{fcntl, OFlag, O_CLOEXEC, FD_CLOEXEC};
+use {Errno, Error, Result, NixPath, at_rawfd};
+use fcntl::{fcntl, AtFlags, OFlag, O_CLOEXEC, FD_CLOEXEC};
 use fcntl::FcntlArg::F_SETFD;
 use libc::{self, c_char, c_void, c_int, c_uint, size_t, pid_t, off_t, uid_t, gid_t, mode_t};
 use std::mem;
@@ -305,6 +305,18 @@ pub fn mkdir<P: ?Sized + NixPath>(path: &P, mode: Mode) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Creates new directory `path` relative to the directory `dirfd`, or to
+/// the current working directory if it is `None`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/mkdirat.2.html)
+pub fn mkdirat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, mode: Mode) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::mkdirat(at_rawfd(dirfd), cstr.as_ptr(), mode.bits() as mode_t) }
+    }));
+
+    Errno::result(res).map(drop)
+}
+
 /// Returns the current directory as a PathBuf
 ///
 /// Err is returned if the current user doesn't have the permission to read or search a component
@@ -381,6 +393,23 @@ pub fn chown<P: ?Sized + NixPath>(path: &P, owner: Option<uid_t>, group: Option<
     Errno::result(res).map(drop)
 }
 
+/// Like `chown`, for `path` relative to the directory `dirfd`, or to the
+/// current working directory if it is `None`.  With `AT_SYMLINK_NOFOLLOW`,
+/// a symbolic link is changed itself instead of its target.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/fchownat.2.html)
+pub fn fchownat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, owner: Option<uid_t>,
+                                     group: Option<gid_t>, flags: AtFlags) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::fchownat(at_rawfd(dirfd), cstr.as_ptr(),
+                                owner.unwrap_or((0 as uid_t).wrapping_sub(1)),
+                                group.unwrap_or((0 as gid_t).wrapping_sub(1)),
+                                flags.bits()) }
+    }));
+
+    Errno::result(res).map(drop)
+}
+
 fn to_exec_array(args: &[CString]) -> Vec<*const c_char> {
     use std::ptr;
     use libc::c_char;
@@ -703,6 +732,75 @@ pub fn unlink<P: ?Sized + NixPath>(path: &P) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Remove `path` relative to the directory `dirfd`, or to the current
+/// working directory if it is `None`.  With `AT_REMOVEDIR` it removes an
+/// empty directory like `rmdir`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/unlinkat.2.html)
+pub fn unlinkat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, flags: AtFlags) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::unlinkat(at_rawfd(dirfd), cstr.as_ptr(), flags.bits()) }
+    }));
+    Errno::result(res).map(drop)
+}
+
+/// Create the hard link `newpath`, relative to `newdirfd`, to `oldpath`,
+/// relative to `olddirfd`.  `None` stands for the current working
+/// directory.  With `AT_SYMLINK_FOLLOW`, a symbolic link at `oldpath` is
+/// dereferenced.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/linkat.2.html)
+pub fn linkat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(olddirfd: Option<RawFd>, oldpath: &P1,
+                                                        newdirfd: Option<RawFd>, newpath: &P2,
+                                                        flags: AtFlags) -> Result<()> {
+    let res = try!(try!(oldpath.with_nix_path(|old| {
+        newpath.with_nix_path(|new| unsafe {
+            libc::linkat(at_rawfd(olddirfd), old.as_ptr(),
+                         at_rawfd(newdirfd), new.as_ptr(), flags.bits())
+        })
+    })));
+    Errno::result(res).map(drop)
+}
+
+/// Create the symbolic link `linkpath`, relative to the directory `dirfd`
+/// or the current working directory if it is `None`, pointing to `target`.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/symlinkat.2.html)
+pub fn symlinkat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(target: &P1, dirfd: Option<RawFd>,
+                                                           linkpath: &P2) -> Result<()> {
+    let res = try!(try!(target.with_nix_path(|t| {
+        linkpath.with_nix_path(|l| unsafe {
+            libc::symlinkat(t.as_ptr(), at_rawfd(dirfd), l.as_ptr())
+        })
+    })));
+    Errno::result(res).map(drop)
+}
+
+libc_bitflags!{
+    /// Which accesses `faccessat` checks for.  `F_OK` only checks that the
+    /// file exists.
+    pub flags AccessFlags: c_int {
+        F_OK,
+        R_OK,
+        W_OK,
+        X_OK,
+    }
+}
+
+/// Check whether the real user and group may access `path`, relative to
+/// the directory `dirfd` or the current working directory if it is
+/// `None`, as `mode` describes.  With `AT_SYMLINK_NOFOLLOW`, a symbolic
+/// link is checked itself instead of its target.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/faccessat.2.html)
+pub fn faccessat<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, mode: AccessFlags,
+                                      flags: AtFlags) -> Result<()> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::faccessat(at_rawfd(dirfd), cstr.as_ptr(), mode.bits(), flags.bits()) }
+    }));
+    Errno::result(res).map(drop)
+}
+
 #[inline]
 pub fn chroot<P: ?Sized + NixPath>(path: &P) -> Result<()> {
     let res = try!(path.with_nix_path(|cstr| {
@@ -9,7 +9,19 @@ mod test_ioctl;
 mod test_wait;
 mod test_select;
 mod test_uio;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos",
+          target_os = "ios", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd"))]
+mod test_xattr;
 
 #[cfg(target_os = "linux")]
 mod test_epoll;
+#[cfg(target_os = "linux")]
+mod test_netlink;
 mod test_pthread;
+#[cfg(all(target_os = "linux",
+          any(target_arch = "x86",
+              target_arch = "x86_64",
+              target_arch = "arm",
+              target_arch = "aarch64")))]
+mod test_ptrace;
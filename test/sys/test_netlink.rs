@@ -0,0 +1,87 @@
+use nix::sys::socket::netlink::{NetlinkSocket, NETLINK_ROUTE};
+use nix::sys::socket::netlink::route::*;
+use std::net::{IpAddr, Ipv4Addr};
+use in_new_netns;
+
+#[test]
+fn test_links() {
+    in_new_netns("test_links", || {
+        let mut rtnl = RtNetlink::new().unwrap();
+        let links = rtnl.links().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].name, "lo");
+        assert!(!links[0].is_up());
+
+        let lo = rtnl.link_by_name("lo").unwrap();
+        assert_eq!(lo, links[0]);
+        rtnl.set_link_up(lo.index, true).unwrap();
+        rtnl.set_link_mtu(lo.index, 1500).unwrap();
+        let lo = rtnl.link_by_name("lo").unwrap();
+        assert!(lo.is_up());
+        assert_eq!(lo.mtu, Some(1500));
+
+        assert!(rtnl.link_by_name("nonexistent0").is_err());
+    });
+}
+
+#[test]
+fn test_veth() {
+    in_new_netns("test_veth", || {
+        let mut rtnl = RtNetlink::new().unwrap();
+        rtnl.create_veth("nixveth0", "nixveth1").unwrap();
+        let veth0 = rtnl.link_by_name("nixveth0").unwrap();
+        let veth1 = rtnl.link_by_name("nixveth1").unwrap();
+        assert_eq!(veth0.kind, Some("veth".to_owned()));
+        assert_eq!(veth0.address.len(), 6);
+
+        rtnl.set_link_name(veth1.index, "nixpeer").unwrap();
+        assert_eq!(rtnl.link_by_name("nixpeer").unwrap().index, veth1.index);
+
+        // Deleting one end removes the pair
+        rtnl.delete_link(veth0.index).unwrap();
+        assert_eq!(rtnl.links().unwrap().len(), 1);
+    });
+}
+
+#[test]
+fn test_addresses_and_routes() {
+    in_new_netns("test_addresses_and_routes", || {
+        let mut rtnl = RtNetlink::new().unwrap();
+        let mut notifications = NetlinkSocket::new(NETLINK_ROUTE, RTMGRP_IPV4_IFADDR)
+            .unwrap();
+        rtnl.create_veth("nixveth0", "nixveth1").unwrap();
+        let veth = rtnl.link_by_name("nixveth0").unwrap();
+        let peer = rtnl.link_by_name("nixveth1").unwrap();
+        rtnl.set_link_up(veth.index, true).unwrap();
+        rtnl.set_link_up(peer.index, true).unwrap();
+
+        let addr = IpAddr::V4(Ipv4Addr::new(10, 42, 0, 1));
+        rtnl.add_address(veth.index, &addr, 24).unwrap();
+        let found = rtnl.addresses().unwrap().into_iter()
+            .find(|a| a.index == veth.index)
+            .expect("address not found");
+        assert_eq!(found.local, Some(addr));
+        assert_eq!(found.prefix_len, 24);
+
+        // The new address is announced to the subscribed socket
+        let announced = notifications.recv().unwrap()
+            .filter_map(|msg| Address::parse(&msg))
+            .any(|a| a.local == Some(addr));
+        assert!(announced);
+
+        let dst = IpAddr::V4(Ipv4Addr::new(10, 43, 0, 0));
+        let gateway = IpAddr::V4(Ipv4Addr::new(10, 42, 0, 2));
+        rtnl.add_route(&dst, 16, Some(&gateway), Some(veth.index)).unwrap();
+        let has_route = |rtnl: &mut RtNetlink| {
+            rtnl.routes().unwrap().iter().any(|r| {
+                r.dst == Some(dst) && r.dst_len == 16 && r.gateway == Some(gateway)
+            })
+        };
+        assert!(has_route(&mut rtnl));
+        rtnl.del_route(&dst, 16, Some(&gateway), Some(veth.index)).unwrap();
+        assert!(!has_route(&mut rtnl));
+
+        rtnl.del_address(veth.index, &addr, 24).unwrap();
+        assert!(rtnl.addresses().unwrap().iter().all(|a| a.local != Some(addr)));
+    });
+}
@@ -0,0 +1,156 @@
+use nix::unistd::*;
+use nix::unistd::ForkResult::*;
+use nix::sys::ptrace;
+use nix::sys::signal::*;
+use nix::sys::wait::*;
+use libc::{self, c_long};
+
+#[test]
+fn test_ptrace_cont() {
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            // As recommended by ptrace(2), raise SIGTRAP to pause the child
+            // until the parent is ready to continue
+            raise(SIGTRAP).unwrap();
+            unsafe { libc::_exit(0) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            ptrace::cont(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_ptrace_peek_poke() {
+    let mut word: c_long = 0x1234;
+    let addr = &mut word as *mut c_long as ptrace::AddressType;
+
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            raise(SIGTRAP).unwrap();
+            // The parent has overwritten our copy of `word` by now
+            let status = if unsafe { ::std::ptr::read_volatile(&word) } == 0x5678 { 0 } else { 1 };
+            unsafe { libc::_exit(status) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            assert_eq!(ptrace::peekdata(child, addr), Ok(0x1234));
+            ptrace::pokedata(child, addr, 0x5678).unwrap();
+            assert_eq!(ptrace::peekdata(child, addr), Ok(0x5678));
+            ptrace::cont(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_ptrace_kill() {
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            raise(SIGTRAP).unwrap();
+            unsafe { libc::_exit(0) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            ptrace::kill(child).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Signaled(child, SIGKILL, false)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn test_ptrace_getregs() {
+    use nix::sys::ptrace::NtPrstatus;
+
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            raise(SIGTRAP).unwrap();
+            unsafe { libc::_exit(0) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            let regs = ptrace::getregs(child).unwrap();
+            let regset = ptrace::getregset::<NtPrstatus>(child).unwrap();
+            assert_eq!(regs.rip, regset.rip);
+            assert_eq!(regs.rsp, regset.rsp);
+
+            // Writing back the same registers must leave the tracee intact
+            ptrace::setregs(child, regs).unwrap();
+            let fpregs = ptrace::getfpregs(child).unwrap();
+            ptrace::setfpregs(child, fpregs).unwrap();
+
+            ptrace::cont(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_ptrace_event_exit() {
+    use nix::sys::ptrace::PtraceEvent;
+    use nix::sys::ptrace::ptrace::{PTRACE_O_TRACEEXIT, PTRACE_EVENT_EXIT};
+
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            raise(SIGTRAP).unwrap();
+            unsafe { libc::_exit(3) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            let siginfo = ptrace::getsiginfo(child).unwrap();
+            assert_eq!(siginfo.si_signo, libc::c_int::from(SIGTRAP));
+
+            ptrace::ptrace_setoptions(child, PTRACE_O_TRACEEXIT).unwrap();
+            ptrace::cont(child, None).unwrap();
+            assert_eq!(waitpid(child, None),
+                       Ok(WaitStatus::PtraceEvent(child, SIGTRAP, PTRACE_EVENT_EXIT)));
+            assert_eq!(PtraceEvent::from_c_int(PTRACE_EVENT_EXIT), Ok(PtraceEvent::Exit));
+            // The event message of an exit stop is the wait status of the tracee
+            assert_eq!(ptrace::getevent(child), Ok(3 << 8));
+
+            ptrace::cont(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 3)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_ptrace_syscall() {
+    use nix::sys::ptrace::ptrace::PTRACE_O_TRACESYSGOOD;
+
+    match fork() {
+        Ok(Child) => {
+            ptrace::traceme().unwrap();
+            raise(SIGTRAP).unwrap();
+            getpid();
+            unsafe { libc::_exit(0) };
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, SIGTRAP)));
+            ptrace::ptrace_setoptions(child, PTRACE_O_TRACESYSGOOD).unwrap();
+
+            // Syscall entry, then syscall exit
+            ptrace::syscall(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::PtraceSyscall(child)));
+            ptrace::syscall(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::PtraceSyscall(child)));
+
+            ptrace::detach(child, None).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
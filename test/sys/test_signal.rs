@@ -1,7 +1,72 @@
 use nix::unistd::*;
+use nix::unistd::ForkResult::*;
 use nix::sys::signal::*;
+use nix::sys::wait::*;
 
 #[test]
 fn test_kill_none() {
     kill(getpid(), None).ok().expect("Should be able to send signal to myself.");
 }
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_realtime_signal() {
+    let signal = Signal::realtime(1).unwrap();
+    assert_eq!(::libc::c_int::from(signal), sigrtmin() + 1);
+    assert_eq!(Signal::from_c_int(sigrtmin() + 1), Ok(signal));
+    assert!(Signal::realtime(sigrtmax() - sigrtmin() + 1).is_err());
+
+    let mut mask = SigSet::empty();
+    mask.add(signal);
+    assert!(mask.contains(signal));
+    assert!(!mask.contains(Signal::realtime(0).unwrap()));
+
+    // sigqueue is process-directed, so send it in a child, where it can't be
+    // delivered to another test's thread
+    match fork() {
+        Ok(Child) => {
+            let received = mask.thread_block().is_ok() &&
+                sigqueue(getpid(), signal, 42).is_ok() &&
+                mask.wait() == Ok(signal);
+            unsafe { ::libc::_exit(if received { 0 } else { 1 }) }
+        },
+        Ok(Parent { child }) => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+        Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_signal_stack() {
+    let previous = SignalStack::current().unwrap();
+    {
+        let stack = SignalStack::new(32 * 1024).unwrap();
+        assert!(stack.stack().size() >= 32 * 1024);
+
+        let current = SignalStack::current().unwrap();
+        assert_eq!(current.base(), stack.stack().base());
+        assert_eq!(current.size(), stack.stack().size());
+        assert!(!current.flags().contains(SS_DISABLE));
+    }
+    let restored = SignalStack::current().unwrap();
+    assert_eq!(restored.base(), previous.base());
+    assert_eq!(restored.flags(), previous.flags());
+}
+
+// Dropping a stack that is no longer installed must leave the current one
+// alone and leak its memory instead of unmapping it.
+#[test]
+fn test_signal_stack_out_of_order() {
+    let previous = SignalStack::current().unwrap();
+    let outer = SignalStack::new(32 * 1024).unwrap();
+    let inner = SignalStack::new(32 * 1024).unwrap();
+    let outer_stack = outer.stack();
+    drop(outer);
+    assert_eq!(SignalStack::current().unwrap().base(), inner.stack().base());
+
+    // The inner stack reinstalls the leaked outer one, which is still mapped
+    drop(inner);
+    assert_eq!(SignalStack::current().unwrap().base(), outer_stack.base());
+    unsafe { sigaltstack(Some(&previous)) }.unwrap();
+}
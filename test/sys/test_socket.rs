@@ -145,6 +145,374 @@ pub fn test_scm_rights() {
     close(w).unwrap();
 }
 
+#[cfg(all(any(target_os = "linux", target_os = "android"), not(target_arch = "arm")))]
+#[test]
+pub fn test_scm_credentials() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{close, getpid, getuid, getgid};
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg, setsockopt,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, CmsgSpace, MsgFlags, ucred};
+    use nix::sys::socket::sockopt::PassCred;
+
+    let (send, recv) = socketpair(AddressFamily::Unix, SockType::Stream, 0,
+                                  SockFlag::empty())
+                       .unwrap();
+    setsockopt(recv, PassCred, &true).unwrap();
+
+    {
+        let iov = [IoVec::from_slice(b"hello")];
+        let cred = ucred {
+            pid: getpid(),
+            uid: getuid(),
+            gid: getgid(),
+        };
+        let cmsg = ControlMessage::ScmCredentials(cred);
+        assert_eq!(sendmsg(send, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+        close(send).unwrap();
+    }
+
+    {
+        let mut buf = [0u8; 5];
+        let iov = [IoVec::from_mut_slice(&mut buf[..])];
+        let mut cmsgspace: CmsgSpace<ucred> = CmsgSpace::new();
+        let msg = recvmsg(recv, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+        let mut received_cred = None;
+
+        for cmsg in msg.cmsgs() {
+            if let ControlMessage::ScmCredentials(cred) = cmsg {
+                assert!(received_cred.is_none());
+                received_cred = Some(cred);
+            } else {
+                panic!("unexpected cmsg");
+            }
+        }
+        let cred = received_cred.expect("no credentials received");
+        assert_eq!(cred.pid, getpid());
+        assert_eq!(cred.uid, getuid());
+        assert_eq!(cred.gid, getgid());
+        close(recv).unwrap();
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_ip_pktinfo() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{bind, getsockname, socket, sendto, recvmsg,
+                           setsockopt, AddressFamily, SockType, SockFlag,
+                           ControlMessage, CmsgSpace, MsgFlags, SockAddr,
+                           InetAddr, IpAddr, in_pktinfo};
+    use nix::sys::socket::sockopt::{Ipv4PacketInfo, ReceiveTimestamp};
+    use nix::sys::time::TimeVal;
+
+    let loopback = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let rsock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+    bind(rsock, &loopback).unwrap();
+    let raddr = getsockname(rsock).unwrap().to_sock_addr().unwrap();
+    setsockopt(rsock, Ipv4PacketInfo, &true).unwrap();
+    setsockopt(rsock, ReceiveTimestamp, &true).unwrap();
+
+    let ssock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+    assert_eq!(sendto(ssock, b"hello", &raddr, MsgFlags::empty()).unwrap(), 5);
+    close(ssock).unwrap();
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace: CmsgSpace<(TimeVal, CmsgSpace<in_pktinfo>)> = CmsgSpace::new();
+    let msg = recvmsg(rsock, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+    let mut got_pktinfo = false;
+    let mut got_timestamp = false;
+
+    for cmsg in msg.cmsgs() {
+        match cmsg {
+            ControlMessage::Ipv4PacketInfo(info) => {
+                assert_eq!(u32::from_be(info.ipi_addr.s_addr), 0x7f000001);
+                assert!(info.ipi_ifindex > 0);
+                got_pktinfo = true;
+            },
+            ControlMessage::ScmTimestamp(_) => got_timestamp = true,
+            _ => panic!("unexpected cmsg"),
+        }
+    }
+    assert!(got_pktinfo);
+    assert!(got_timestamp);
+    close(rsock).unwrap();
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_bpf_filter() {
+    use nix::{Errno, Error};
+    use nix::unistd::close;
+    use nix::sys::socket::{bind, getsockname, socket, sendto, recv,
+                           AddressFamily, SockType, SockFlag, MsgFlags,
+                           SockAddr, InetAddr, IpAddr, MSG_DONTWAIT};
+    use nix::sys::socket::bpf::*;
+
+    let loopback = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let rsock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+    bind(rsock, &loopback).unwrap();
+    let raddr = getsockname(rsock).unwrap().to_sock_addr().unwrap();
+    let ssock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+
+    // Keep datagrams whose payload is longer than 4 bytes.  The filter
+    // sees the UDP packet without the IP header, so 8 bytes of UDP header.
+    let filter = FilterBuilder::new()
+        .ld_len()
+        .jump(BPF_JGT, 12, None, Some("drop"))
+        .ret(0xffff)
+        .label("drop")
+        .ret(0)
+        .build()
+        .unwrap();
+    attach_filter(rsock, &filter).unwrap();
+
+    let mut buf = [0u8; 16];
+    sendto(ssock, b"drop", &raddr, MsgFlags::empty()).unwrap();
+    sendto(ssock, b"keep me", &raddr, MsgFlags::empty()).unwrap();
+    assert_eq!(recv(rsock, &mut buf, MSG_DONTWAIT).unwrap(), 7);
+    assert_eq!(&buf[..7], b"keep me");
+    assert!(recv(rsock, &mut buf, MSG_DONTWAIT).is_err());
+
+    detach_filter(rsock).unwrap();
+    sendto(ssock, b"drop", &raddr, MsgFlags::empty()).unwrap();
+    assert_eq!(recv(rsock, &mut buf, MSG_DONTWAIT).unwrap(), 4);
+
+    attach_filter(rsock, &filter).unwrap();
+    lock_filter(rsock).unwrap();
+    assert_eq!(detach_filter(rsock), Err(Error::Sys(Errno::EPERM)));
+
+    close(ssock).unwrap();
+    close(rsock).unwrap();
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_sendmmsg_recvmmsg() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{bind, getsockname, socket, sendmmsg, recvmmsg,
+                           setsockopt, AddressFamily, SockType, SockFlag,
+                           ControlMessage, MsgFlags, MultiHeaders, SockAddr,
+                           SockaddrStorage, InetAddr, IpAddr, in_pktinfo,
+                           MSG_DONTWAIT};
+    use nix::sys::socket::sockopt::Ipv4PacketInfo;
+    use nix::sys::time::{TimeSpec, TimeValLike};
+
+    let loopback = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0));
+    let rsock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+    bind(rsock, &loopback).unwrap();
+    let raddr = getsockname(rsock).unwrap().to_sock_addr().unwrap();
+    setsockopt(rsock, Ipv4PacketInfo, &true).unwrap();
+    let ssock = socket(AddressFamily::Inet, SockType::Datagram,
+                       SockFlag::empty(), 0).unwrap();
+    bind(ssock, &loopback).unwrap();
+    let saddr = getsockname(ssock).unwrap();
+
+    let msgs: [&[u8]; 3] = [b"one", b"two", b"three"];
+    let slices: Vec<[IoVec<&[u8]>; 1]> = msgs.iter()
+        .map(|m| [IoVec::from_slice(m)])
+        .collect();
+    let addrs = vec![Some(raddr); msgs.len()];
+    let cmsgs: Vec<&[ControlMessage]> = Vec::new();
+    let mut send_headers = MultiHeaders::<()>::new(msgs.len());
+    let mut recv_headers = MultiHeaders::<in_pktinfo>::new(4);
+    assert_eq!(recv_headers.len(), 4);
+    assert!(!recv_headers.is_empty());
+    assert!(MultiHeaders::<()>::new(0).is_empty());
+
+    // Run two batches to make sure the headers can be reused.
+    for _ in 0..2 {
+        let sent: Vec<usize> = sendmmsg(ssock, &mut send_headers, &slices,
+                                        &addrs, &cmsgs, MsgFlags::empty())
+            .unwrap()
+            .collect();
+        assert_eq!(sent, vec![3, 3, 5]);
+
+        let mut bufs = [[0u8; 8]; 4];
+        let received: Vec<(usize, bool, Option<SockaddrStorage>)> = {
+            let slices: Vec<[IoVec<&mut [u8]>; 1]> = bufs.iter_mut()
+                .map(|b| [IoVec::from_mut_slice(&mut b[..])])
+                .collect();
+            // Only three of the four slots can be filled, so don't block
+            // waiting for the fourth.
+            recvmmsg(rsock, &mut recv_headers, &slices, MSG_DONTWAIT,
+                     Some(TimeSpec::milliseconds(10)))
+                .unwrap()
+                .map(|msg| {
+                    let pktinfo = msg.cmsgs().any(|cmsg| {
+                        match cmsg {
+                            ControlMessage::Ipv4PacketInfo(_) => true,
+                            _ => false,
+                        }
+                    });
+                    (msg.bytes, pktinfo, msg.address)
+                })
+                .collect()
+        };
+        assert_eq!(received.len(), 3);
+        for (i, &(bytes, pktinfo, ref address)) in received.iter().enumerate() {
+            assert_eq!(&bufs[i][..bytes], msgs[i]);
+            assert!(pktinfo);
+            assert!(address.as_ref() == Some(&saddr));
+        }
+    }
+
+    close(ssock).unwrap();
+    close(rsock).unwrap();
+}
+
+// Addresses of families SockAddr doesn't know about, and unnamed Unix
+// addresses, must still be returned.
+#[test]
+pub fn test_sockaddr_storage() {
+    use nix::sys::socket::{socketpair, getsockname, getpeername,
+                           AddressFamily, SockType, SockFlag};
+    use nix::unistd::close;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, 0,
+                                SockFlag::empty())
+                     .unwrap();
+    let addr = getsockname(fd1).unwrap();
+    assert_eq!(addr.family(), Some(AddressFamily::Unix));
+    let unix = addr.as_unix().unwrap();
+    assert!(unix.path().is_none());
+    assert_eq!(addr.to_str(), "<unbound UNIX socket>");
+    assert_eq!(getpeername(fd1).unwrap(), getsockname(fd2).unwrap());
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+// A length that doesn't fit the family must fail the conversion instead of
+// panicking or reading past the address.
+#[test]
+pub fn test_sockaddr_storage_bad_len() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{SockAddr, SockaddrStorage};
+
+    let inet = SockAddr::new_inet(InetAddr::from_std(&"127.0.0.1:3000".parse().unwrap()));
+    let ss = *SockaddrStorage::from(inet).as_ref();
+    let short = SockaddrStorage::from_raw(ss, 4);
+    assert_eq!(short.to_sock_addr().err(), Some(Error::Sys(Errno::EINVAL)));
+    assert!(short.as_inet().is_none());
+    assert_eq!(short.to_str(), format!("<address family {}>", ss.ss_family));
+
+    let unix = SockAddr::new_unix("/tmp/nix-test").unwrap();
+    let ss = *SockaddrStorage::from(unix).as_ref();
+    let len = mem::size_of::<::libc::sockaddr_storage>() as ::libc::socklen_t;
+    let long = SockaddrStorage::from_raw(ss, len);
+    assert_eq!(long.to_sock_addr().err(), Some(Error::Sys(Errno::EINVAL)));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_sockaddr_storage_bad_netlink_len() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{SockAddr, SockaddrStorage};
+
+    let netlink = SockAddr::new_netlink(0, 0);
+    let ss = *SockaddrStorage::from(netlink).as_ref();
+    let short = SockaddrStorage::from_raw(ss, 4);
+    assert_eq!(short.to_sock_addr().err(), Some(Error::Sys(Errno::EINVAL)));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_sockaddr_storage_unknown_family() {
+    use nix::sys::socket::{socket, getsockname, AddressFamily, SockType,
+                           SockFlag, AF_PACKET};
+    use nix::unistd::close;
+
+    // Creating packet sockets requires CAP_NET_RAW
+    if let Ok(fd) = socket(AddressFamily::Packet, SockType::Datagram,
+                           SockFlag::empty(), 0) {
+        let addr = getsockname(fd).unwrap();
+        assert_eq!(addr.raw_family().map(|af| af as i32), Some(AF_PACKET));
+        assert!(addr.to_sock_addr().is_err());
+        assert!(addr.as_inet().is_none());
+        close(fd).unwrap();
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_link_addr() {
+    use nix::sys::socket::{LinkAddr, SockAddr, SockaddrStorage, AddressFamily,
+                           ETH_P_IP};
+
+    let hwaddr = [0x02, 0x00, 0x5e, 0x10, 0x00, 0x01];
+    let addr = LinkAddr::new(ETH_P_IP, 3, &hwaddr).unwrap();
+    assert_eq!(addr.protocol(), ETH_P_IP);
+    assert_eq!(addr.ifindex(), 3);
+    assert_eq!(addr.addr(), &hwaddr[..]);
+    assert_eq!(addr.to_string(), "ifindex: 3 protocol: 0x0800 addr: 02:00:5e:10:00:01");
+    assert!(LinkAddr::new(ETH_P_IP, 3, &[0; 9]).is_err());
+
+    let sockaddr = SockAddr::new_link(addr);
+    assert_eq!(sockaddr.family(), AddressFamily::Packet);
+    let storage = SockaddrStorage::from(sockaddr);
+    assert_eq!(storage.family(), Some(AddressFamily::Packet));
+    assert!(storage.as_link() == Some(addr));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_packet_socket() {
+    use nix::sys::socket::{bind, getsockname, recvfrom, sendto, setsockopt,
+                           socket, AddressFamily, SockType, SockFlag, SockAddr,
+                           LinkAddr, MsgFlags, packet_mreq, ETH_P_ALL,
+                           PACKET_MR_PROMISC, PACKET_FANOUT_HASH};
+    use nix::sys::socket::sockopt::{PacketAddMembership, PacketFanout, ReceiveTimeout};
+    use nix::sys::time::{TimeVal, TimeValLike};
+    use nix::unistd::close;
+
+    // Packet sockets require CAP_NET_RAW
+    let fd = match socket(AddressFamily::Packet, SockType::Datagram,
+                          SockFlag::empty(), (ETH_P_ALL.to_be()) as i32) {
+        Ok(fd) => fd,
+        Err(_) => return,
+    };
+    // The loopback interface is always present, and is the first one.
+    let lo = LinkAddr::new(ETH_P_ALL, 1, &[]).unwrap();
+    bind(fd, &SockAddr::new_link(lo)).unwrap();
+    let bound = getsockname(fd).unwrap().as_link().unwrap();
+    assert_eq!(bound.ifindex(), 1);
+    assert_eq!(bound.protocol(), ETH_P_ALL);
+
+    let mreq = packet_mreq::new(1, PACKET_MR_PROMISC, &[]);
+    setsockopt(fd, PacketAddMembership, &mreq).unwrap();
+    setsockopt(fd, PacketFanout, &(0x4242 | (PACKET_FANOUT_HASH << 16))).unwrap();
+    // Don't wait forever for our frame on a busy or filtered interface
+    setsockopt(fd, ReceiveTimeout, &TimeVal::seconds(5)).unwrap();
+
+    let dst = LinkAddr::new(0x88b5, 1, &[0; 6]).unwrap();
+    sendto(fd, b"hello", &SockAddr::new_link(dst), MsgFlags::empty()).unwrap();
+    let mut buf = [0u8; 64];
+    let mut received = false;
+    for _ in 0..1000 {
+        let (len, from) = recvfrom(fd, &mut buf)
+            .expect("timed out waiting for the sent frame");
+        let from = from.as_link().unwrap();
+        if from.protocol() == 0x88b5 {
+            assert_eq!(&buf[..len], b"hello");
+            assert_eq!(from.ifindex(), 1);
+            received = true;
+            break;
+        }
+    }
+    assert!(received, "the sent frame was not among the first 1000 received");
+    close(fd).unwrap();
+}
+
 // Test creating and using named unix domain sockets
 #[test]
 pub fn test_unixdomain() {
@@ -190,3 +190,37 @@ fn test_preadv() {
     let all = buffers.concat();
     assert_eq!(all, expected);
 }
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn test_process_vm_readv() {
+    let remote: Vec<u8> = (0..100).collect();
+    let mut buf = vec![0u8; 60];
+
+    // Reading our own memory is always permitted
+    let remote_iov = [RemoteIoVec { base: remote.as_ptr() as usize + 10, len: 40 },
+                      RemoteIoVec { base: remote.as_ptr() as usize + 80, len: 20 }];
+    {
+        let (first, second) = buf.split_at_mut(30);
+        let local_iov = [IoVec::from_mut_slice(first), IoVec::from_mut_slice(second)];
+        assert_eq!(Ok(60), process_vm_readv(getpid(), &local_iov, &remote_iov));
+    }
+
+    let expected: Vec<u8> = (10..50).chain(80..100).collect();
+    assert_eq!(buf, expected);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn test_process_vm_writev() {
+    let mut remote = vec![0u8; 32];
+    let data: Vec<u8> = (0..16).collect();
+
+    let remote_iov = [RemoteIoVec { base: remote.as_mut_ptr() as usize + 8, len: 16 }];
+    let local_iov = [IoVec::from_slice(&data)];
+    assert_eq!(Ok(16), process_vm_writev(getpid(), &local_iov, &remote_iov));
+
+    assert_eq!(&remote[..8], &[0u8; 8][..]);
+    assert_eq!(&remote[8..24], &data[..]);
+    assert_eq!(&remote[24..], &[0u8; 8][..]);
+}
@@ -28,3 +28,60 @@ fn test_wait_exit() {
       Err(_) => panic!("Error: Fork Failed")
     }
 }
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_waitid_nowait() {
+    match fork() {
+      Ok(Child) => unsafe { exit(12); },
+      Ok(Parent { child }) => {
+          // Without one of WEXITED, WSTOPPED and WCONTINUED there is nothing
+          // to wait for
+          assert_eq!(waitid(Id::Pid(child), WNOWAIT).err(),
+                     Some(::nix::Error::Sys(::nix::errno::Errno::EINVAL)));
+
+          // WNOWAIT leaves the child waitable, so it can be reaped afterwards
+          let info = waitid(Id::Pid(child), WEXITED | WNOWAIT).unwrap().unwrap();
+          assert_eq!(info.pid(), child);
+          assert_eq!(info.uid(), getuid());
+          assert_eq!(info.status(), Ok(WaitStatus::Exited(child, 12)));
+          assert_eq!(info.siginfo().si_signo, ::libc::c_int::from(SIGCHLD));
+
+          assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 12)));
+      },
+      // panic, fork should never fail unless there is a serious problem with the OS
+      Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_waitid_nohang() {
+    match fork() {
+      Ok(Child) => pause().unwrap_or(()),
+      Ok(Parent { child }) => {
+          assert!(waitid(Id::Pid(child), WEXITED | WNOHANG).unwrap().is_none());
+
+          kill(child, Some(SIGKILL)).ok().expect("Error: Kill Failed");
+          let info = waitid(Id::Pid(child), WEXITED).unwrap().unwrap();
+          assert_eq!(info.status(), Ok(WaitStatus::Signaled(child, SIGKILL, false)));
+      },
+      // panic, fork should never fail unless there is a serious problem with the OS
+      Err(_) => panic!("Error: Fork Failed")
+    }
+}
+
+#[test]
+fn test_wait4() {
+    match fork() {
+      Ok(Child) => unsafe { exit(12); },
+      Ok(Parent { child }) => {
+          let (status, rusage) = wait4(child, None).unwrap();
+          assert_eq!(status, WaitStatus::Exited(child, 12));
+          // The child had at least its inherited pages resident
+          assert!(rusage.ru_maxrss > 0);
+      },
+      // panic, fork should never fail unless there is a serious problem with the OS
+      Err(_) => panic!("Error: Fork Failed")
+    }
+}
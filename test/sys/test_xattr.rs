@@ -0,0 +1,80 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::os::unix::fs::symlink;
+use std::os::unix::io::AsRawFd;
+
+use nix::Error;
+use nix::errno::{self, Errno};
+use nix::sys::xattr::*;
+use tempdir::TempDir;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const ENOATTR: Errno = Errno::ENODATA;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const ENOATTR: Errno = Errno::ENOATTR;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const ENOTSUP: Errno = Errno::ENOTSUP;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const ENOTSUP: Errno = errno::EOPNOTSUPP;
+
+#[test]
+fn test_xattr() {
+    let tempdir = TempDir::new("nix-test_xattr").unwrap();
+    let path = tempdir.path().join("file");
+    let file = File::create(&path).unwrap();
+
+    // Not every file system supports user attributes
+    match setxattr(&path, "user.nix", b"value", XattrFlags::empty()) {
+        Err(Error::Sys(e)) if e == ENOTSUP => return,
+        res => res.unwrap(),
+    }
+    assert_eq!(getxattr(&path, "user.nix").unwrap(), b"value");
+    assert_eq!(fgetxattr(file.as_raw_fd(), "user.nix").unwrap(), b"value");
+
+    assert_eq!(setxattr(&path, "user.nix", b"other", XATTR_CREATE),
+               Err(Error::Sys(Errno::EEXIST)));
+    assert_eq!(setxattr(&path, "user.missing", b"other", XATTR_REPLACE),
+               Err(Error::Sys(ENOATTR)));
+
+    // Values larger than the first guess of any buffer size, and empty ones
+    let large = vec![0x5a; 2000];
+    fsetxattr(file.as_raw_fd(), "user.large", &large, XATTR_CREATE).unwrap();
+    assert_eq!(getxattr(&path, "user.large").unwrap(), large);
+    setxattr(&path, "user.empty", b"", XattrFlags::empty()).unwrap();
+    assert_eq!(getxattr(&path, "user.empty").unwrap(), b"");
+
+    let names: Vec<OsString> = listxattr(&path).unwrap().collect();
+    for name in &["user.nix", "user.large", "user.empty"] {
+        assert!(names.contains(&OsString::from(*name)), "{} missing from {:?}", name, names);
+    }
+    assert_eq!(flistxattr(file.as_raw_fd()).unwrap().count(), names.len());
+
+    removexattr(&path, "user.nix").unwrap();
+    fremovexattr(file.as_raw_fd(), "user.large").unwrap();
+    assert_eq!(getxattr(&path, "user.nix"), Err(Error::Sys(ENOATTR)));
+    assert_eq!(removexattr(&path, "user.large"), Err(Error::Sys(ENOATTR)));
+    let names: Vec<OsString> = listxattr(&path).unwrap().collect();
+    assert!(!names.contains(&OsString::from("user.nix")));
+}
+
+#[test]
+fn test_xattr_symlink() {
+    let tempdir = TempDir::new("nix-test_xattr_symlink").unwrap();
+    let path = tempdir.path().join("file");
+    let link = tempdir.path().join("link");
+    File::create(&path).unwrap();
+    symlink("file", &link).unwrap();
+
+    match setxattr(&link, "user.nix", b"target", XattrFlags::empty()) {
+        Err(Error::Sys(e)) if e == ENOTSUP => return,
+        res => res.unwrap(),
+    }
+    // The attribute went to the target, not to the link
+    assert_eq!(getxattr(&path, "user.nix").unwrap(), b"target");
+    assert_eq!(lgetxattr(&link, "user.nix"), Err(Error::Sys(ENOATTR)));
+    assert!(llistxattr(&link).unwrap().all(|name| name != OsString::from("user.nix")));
+
+    lremovexattr(&path, "user.nix").unwrap();
+    assert_eq!(getxattr(&link, "user.nix"), Err(Error::Sys(ENOATTR)));
+}
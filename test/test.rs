@@ -10,6 +10,7 @@ extern crate tempfile;
 extern crate nix_test as nixtest;
 
 mod sys;
+mod test_dir;
 mod test_fcntl;
 #[cfg(any(target_os = "linux"))]
 mod test_mq;
@@ -25,6 +26,27 @@ mod test_unistd;
 
 use nixtest::assert_size_of;
 
+/// Run `f` on a new thread in a new network namespace, which only contains a
+/// loopback interface.  Without the privileges to create one, `test` is
+/// reported as skipped on stderr and `f` is not run.
+#[cfg(target_os = "linux")]
+pub fn in_new_netns<F: FnOnce() + Send + 'static>(test: &'static str, f: F) {
+    use nix::sched::{unshare, CLONE_NEWNET};
+    use std::io::{self, Write};
+    use std::thread;
+
+    thread::spawn(move || {
+        match unshare(CLONE_NEWNET) {
+            Ok(()) => f(),
+            Err(e) => {
+                let stderr = io::stderr();
+                writeln!(stderr.lock(), "{}: skipped, unshare(CLONE_NEWNET) failed: {}",
+                         test, e).unwrap();
+            },
+        }
+    }).join().unwrap();
+}
+
 #[test]
 pub fn test_size_of_long() {
     // This test is mostly here to ensure that 32bit CI is correctly
@@ -0,0 +1,51 @@
+use nix::dir::{Dir, Type};
+use nix::fcntl::{openat, O_CREAT, O_RDONLY, O_WRONLY};
+use nix::sys::stat::{Mode, S_IRUSR, S_IRWXU};
+use nix::unistd::{close, mkdir};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use tempdir::TempDir;
+
+#[test]
+fn test_dir() {
+    let tmp = TempDir::new("nix-test_dir").unwrap();
+    mkdir(&tmp.path().join("sub"), S_IRWXU).unwrap();
+    let mut dir = Dir::open(tmp.path(), O_RDONLY, Mode::empty()).unwrap();
+    let fd = openat(dir.as_raw_fd(), "file", O_CREAT | O_WRONLY, S_IRUSR).unwrap();
+    close(fd).unwrap();
+
+    let mut entries: Vec<_> = dir.iter().map(|e| e.unwrap()).collect();
+    entries.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    let names: Vec<_> = entries.iter().map(|e| e.file_name().to_str().unwrap()).collect();
+    assert_eq!(names, [".", "..", "file", "sub"]);
+
+    for entry in &entries[2..] {
+        let path = tmp.path().join(entry.file_name().to_str().unwrap());
+        assert_eq!(entry.ino(), fs::symlink_metadata(path).unwrap().ino());
+        match entry.file_type() {
+            Some(Type::File) => assert_eq!(entry.file_name().to_bytes(), b"file"),
+            Some(Type::Directory) => assert_eq!(entry.file_name().to_bytes(), b"sub"),
+            other => assert!(other.is_none()),
+        }
+    }
+
+    // The stream is exhausted until it is rewound
+    assert_eq!(dir.iter().count(), 0);
+    dir.rewind();
+    assert_eq!(dir.iter().count(), 4);
+}
+
+#[test]
+fn test_dir_openat() {
+    let tmp = TempDir::new("nix-test_dir_openat").unwrap();
+    mkdir(&tmp.path().join("sub"), S_IRWXU).unwrap();
+    let mut parent = Dir::open(tmp.path(), O_RDONLY, Mode::empty()).unwrap();
+    let mut sub = Dir::openat(parent.as_raw_fd(), "sub", O_RDONLY, Mode::empty()).unwrap();
+    assert_eq!(sub.iter().count(), 2);
+    assert_eq!(parent.iter().count(), 3);
+
+    let fd = openat(parent.as_raw_fd(), "file", O_CREAT | O_WRONLY, S_IRUSR).unwrap();
+    close(fd).unwrap();
+    assert!(Dir::openat(parent.as_raw_fd(), "file", O_RDONLY, Mode::empty()).is_err());
+}
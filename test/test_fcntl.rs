@@ -1,10 +1,12 @@
-use nix::fcntl::{openat, open, OFlag, O_RDONLY, readlink, readlinkat};
+use nix::fcntl::{openat, open, OFlag, O_RDONLY, readlink, readlinkat, renameat};
 use nix::sys::stat::Mode;
 use nix::unistd::{close, read};
 use tempdir::TempDir;
 use tempfile::NamedTempFile;
+use std::fs::File;
 use std::io::prelude::*;
 use std::os::unix::fs;
+use std::os::unix::io::AsRawFd;
 
 #[test]
 fn test_openat() {
@@ -47,6 +49,19 @@ fn test_readlink() {
                src.to_str().unwrap());
 }
 
+#[test]
+fn test_renameat() {
+    let old_dir = TempDir::new("nix-test_renameat").unwrap();
+    let new_dir = TempDir::new("nix-test_renameat").unwrap();
+    let old_dirfd = File::open(old_dir.path()).unwrap();
+    let new_dirfd = File::open(new_dir.path()).unwrap();
+    File::create(old_dir.path().join("old")).unwrap();
+
+    renameat(Some(old_dirfd.as_raw_fd()), "old", Some(new_dirfd.as_raw_fd()), "new").unwrap();
+    assert!(!old_dir.path().join("old").exists());
+    assert!(new_dir.path().join("new").exists());
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod linux_android {
     use std::io::prelude::*;
@@ -54,10 +69,16 @@ mod linux_android {
 
     use libc::loff_t;
 
+    use std::fs::File;
+
+    use nix::Error;
+    use nix::errno::Errno;
     use nix::fcntl::{SpliceFFlags, splice, tee, vmsplice};
+    use nix::fcntl::{renameat2, RenameFlags, RENAME_EXCHANGE, RENAME_NOREPLACE};
     use nix::sys::uio::IoVec;
     use nix::unistd::{close, pipe, read, write};
 
+    use tempdir::TempDir;
     use tempfile::tempfile;
 
     #[test]
@@ -131,4 +152,27 @@ mod linux_android {
         close(wr).unwrap();
     }
 
+    #[test]
+    fn test_renameat2() {
+        let tmpdir = TempDir::new("nix-test_renameat2").unwrap();
+        let dir = File::open(tmpdir.path()).unwrap();
+        let dirfd = Some(dir.as_raw_fd());
+        File::create(tmpdir.path().join("a")).unwrap().write_all(b"a").unwrap();
+        File::create(tmpdir.path().join("b")).unwrap().write_all(b"b").unwrap();
+
+        // Older kernels and some file systems don't support the flags
+        match renameat2(dirfd, "a", dirfd, "b", RENAME_NOREPLACE) {
+            Err(Error::Sys(Errno::EINVAL)) | Err(Error::Sys(Errno::ENOSYS)) => return,
+            res => assert_eq!(res, Err(Error::Sys(Errno::EEXIST))),
+        }
+
+        renameat2(dirfd, "a", dirfd, "b", RENAME_EXCHANGE).unwrap();
+        let mut buf = String::new();
+        File::open(tmpdir.path().join("a")).unwrap().read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "b");
+
+        renameat2(dirfd, "a", dirfd, "c", RenameFlags::empty()).unwrap();
+        assert!(!tmpdir.path().join("a").exists());
+        assert!(tmpdir.path().join("c").exists());
+    }
 }
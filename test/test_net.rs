@@ -10,3 +10,151 @@ const LOOPBACK: &'static [u8] = b"lo0";
 fn test_if_nametoindex() {
     assert!(if_nametoindex(&LOOPBACK[..]).is_ok());
 }
+
+#[test]
+fn test_if_indextoname() {
+    let index = if_nametoindex(&LOOPBACK[..]).unwrap();
+    assert_eq!(if_indextoname(index).unwrap().as_bytes(), LOOPBACK);
+    assert!(if_indextoname(0).is_err());
+}
+
+#[test]
+fn test_if_nameindex() {
+    let index = if_nametoindex(&LOOPBACK[..]).unwrap();
+    let interfaces = if_nameindex().unwrap();
+    assert!(interfaces.iter().any(|i| i.index == index && i.name.as_bytes() == LOOPBACK));
+}
+
+#[test]
+fn test_getifaddrs() {
+    let mut found = false;
+    for ifaddr in getifaddrs().unwrap() {
+        if ifaddr.interface_name.as_bytes() == LOOPBACK {
+            assert!(ifaddr.flags.contains(IFF_LOOPBACK));
+            assert!(ifaddr.flags.contains(IFF_UP));
+            found = true;
+        }
+    }
+    assert!(found);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use nix::net::if_::*;
+    use nix::sys::socket::{socket, AddressFamily, SockType, SockFlag, Ipv4Addr};
+    use nix::unistd::close;
+    use in_new_netns;
+
+    fn control_socket() -> i32 {
+        socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_if_flags_and_mtu() {
+        let sock = control_socket();
+        assert!(if_flags(sock, "lo").unwrap().contains(IFF_LOOPBACK));
+        assert!(if_mtu(sock, "lo").unwrap() > 0);
+        assert!(if_flags(sock, "nonexistent0").is_err());
+        assert!(if_flags(sock, "a_much_too_long_interface_name").is_err());
+        close(sock).unwrap();
+    }
+
+    #[test]
+    fn test_if_configure() {
+        in_new_netns("test_if_configure", || {
+            let sock = control_socket();
+            assert!(!if_flags(sock, "lo").unwrap().contains(IFF_UP));
+            if_set_up(sock, "lo", true).unwrap();
+            assert!(if_flags(sock, "lo").unwrap().contains(IFF_UP));
+            if_set_promisc(sock, "lo", true).unwrap();
+            assert!(if_flags(sock, "lo").unwrap().contains(IFF_PROMISC | IFF_UP));
+            if_set_promisc(sock, "lo", false).unwrap();
+            assert!(!if_flags(sock, "lo").unwrap().contains(IFF_PROMISC));
+
+            if_set_mtu(sock, "lo", 1500).unwrap();
+            assert_eq!(if_mtu(sock, "lo").unwrap(), 1500);
+            assert_eq!(if_hwaddr(sock, "lo").unwrap(), [0; 6]);
+
+            if_set_addr(sock, "lo", &Ipv4Addr::new(127, 0, 0, 2)).unwrap();
+            if_set_netmask(sock, "lo", &Ipv4Addr::new(255, 255, 0, 0)).unwrap();
+            assert_eq!(if_addr(sock, "lo").unwrap().octets(), [127, 0, 0, 2]);
+            assert_eq!(if_netmask(sock, "lo").unwrap().octets(), [255, 255, 0, 0]);
+
+            if_set_up(sock, "lo", false).unwrap();
+            if_set_name(sock, "lo", "nixlo").unwrap();
+            assert!(if_flags(sock, "nixlo").is_ok());
+            assert!(if_flags(sock, "lo").is_err());
+            close(sock).unwrap();
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod tun {
+    use nix::net::if_::{if_nametoindex, if_mtu, if_set_mtu};
+    use nix::net::tun::*;
+    use nix::sys::socket::{socket, AddressFamily, SockType, SockFlag};
+    use nix::unistd::close;
+    use std::io::{self, Write};
+    use std::path::Path;
+
+    // Run `f` in a new network namespace, reporting `test` as skipped if
+    // there is no TUN/TAP support.
+    fn in_tun_netns<F: FnOnce() + Send + 'static>(test: &'static str, f: F) {
+        ::in_new_netns(test, move || {
+            if Path::new(TUN_DEVICE).exists() {
+                f();
+            } else {
+                let stderr = io::stderr();
+                writeln!(stderr.lock(), "{}: skipped, {} does not exist", test, TUN_DEVICE).unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_tap() {
+        in_tun_netns("test_tap", || {
+            let tap = Tun::new("", IFF_TAP | IFF_NO_PI).unwrap();
+            assert!(tap.name().starts_with("tap"));
+            assert!(if_nametoindex(tap.name()).is_ok());
+
+            let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), 0).unwrap();
+            if_set_mtu(sock, tap.name(), 1400).unwrap();
+            assert_eq!(if_mtu(sock, tap.name()).unwrap(), 1400);
+            close(sock).unwrap();
+
+            let name = tap.name().to_owned();
+            drop(tap);
+            assert!(if_nametoindex(&name[..]).is_err());
+        });
+    }
+
+    #[test]
+    fn test_tun_persist() {
+        in_tun_netns("test_tun_persist", || {
+            let tun = Tun::new("nixtun%d", IFF_TUN | IFF_NO_PI).unwrap();
+            assert_eq!(tun.name(), "nixtun0");
+            tun.set_persist(true).unwrap();
+            tun.set_owner(0).unwrap();
+            drop(tun);
+            assert!(if_nametoindex("nixtun0").is_ok());
+
+            let tun = Tun::new("nixtun0", IFF_TUN | IFF_NO_PI).unwrap();
+            tun.set_persist(false).unwrap();
+            drop(tun);
+            assert!(if_nametoindex("nixtun0").is_err());
+        });
+    }
+
+    #[test]
+    fn test_tun_multi_queue() {
+        in_tun_netns("test_tun_multi_queue", || {
+            let flags = IFF_TUN | IFF_NO_PI | IFF_MULTI_QUEUE;
+            let q0 = Tun::new("nixmq0", flags).unwrap();
+            let q1 = Tun::new("nixmq0", flags).unwrap();
+            assert_eq!(q0.name(), q1.name());
+            q1.detach_queue().unwrap();
+            q1.attach_queue().unwrap();
+        });
+    }
+}
@@ -12,7 +12,7 @@ fn main() {
     print!("test test_signalfd ... ");
 
     let mut mask = signal::SigSet::empty();
-    mask.add(signal::SIGUSR1).unwrap();
+    mask.add(signal::SIGUSR1);
     mask.thread_block().unwrap();
 
     let mut fd = SignalFd::new(&mask).unwrap();
@@ -22,7 +22,9 @@ fn main() {
 
     let res = fd.read_signal();
 
-    assert_eq!(res.unwrap().unwrap().ssi_signo as i32, signal::SIGUSR1);
+    let info = signal::SigInfo::from(&res.unwrap().unwrap());
+    assert_eq!(info.signal(), Ok(signal::SIGUSR1));
+    assert_eq!(info.code(), signal::SigCode::User { pid: pid, uid: unistd::getuid() });
     println!("ok");
 }
 
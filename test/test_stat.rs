@@ -110,3 +110,120 @@ fn test_stat_fstat_lstat() {
     let fstat_result = fstat(link.as_raw_fd());
     assert_stat_results(fstat_result);
 }
+
+#[test]
+fn test_fchmodat() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = TempDir::new("nix-test_fchmodat").unwrap();
+    let filename = tempdir.path().join("foo.txt");
+    File::create(&filename).unwrap();
+    let dir = File::open(tempdir.path()).unwrap();
+
+    stat::fchmodat(Some(dir.as_raw_fd()), "foo.txt", stat::S_IRUSR, fcntl::AtFlags::empty()).unwrap();
+    let mode = filename.metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o400);
+}
+
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+#[test]
+fn test_mknodat() {
+    let tempdir = TempDir::new("nix-test_mknodat").unwrap();
+    let dir = File::open(tempdir.path()).unwrap();
+
+    stat::mknodat(Some(dir.as_raw_fd()), "fifo", stat::S_IFIFO, stat::S_IRWXU, 0).unwrap();
+    let st = stat(&tempdir.path().join("fifo")).unwrap();
+    assert_eq!(st.st_mode as u32 & S_IFMT as u32, stat::S_IFIFO.bits() as u32);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+#[test]
+fn test_utimensat() {
+    use nix::sys::time::{TimeSpec, TimeValLike};
+
+    let tempdir = TempDir::new("nix-test_utimensat").unwrap();
+    let filename = tempdir.path().join("foo.txt");
+    File::create(&filename).unwrap();
+    let dir = File::open(tempdir.path()).unwrap();
+    let dirfd = Some(dir.as_raw_fd());
+
+    let atime = TimeSpec::seconds(12345) + TimeSpec::nanoseconds(678);
+    let mtime = TimeSpec::seconds(54321);
+    stat::utimensat(dirfd, "foo.txt", &atime, &mtime, fcntl::AtFlags::empty()).unwrap();
+    let st = stat(&filename).unwrap();
+    assert_eq!(st.st_atime, 12345);
+    assert_eq!(st.st_atime_nsec, 678);
+    assert_eq!(st.st_mtime, 54321);
+
+    // Only touch the modification time
+    stat::utimensat(dirfd, "foo.txt", &stat::UTIME_OMIT, &stat::UTIME_NOW, fcntl::AtFlags::empty()).unwrap();
+    let st = stat(&filename).unwrap();
+    assert_eq!(st.st_atime, 12345);
+    assert!(st.st_mtime > 54321);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_statx() {
+    use std::io::Write;
+    use nix::sys::stat::{statx, STATX_BASIC_STATS, STATX_BTIME};
+
+    let tempdir = TempDir::new("nix-test_statx").unwrap();
+    let filename = tempdir.path().join("foo.txt");
+    File::create(&filename).unwrap().write_all(b"hello").unwrap();
+    let dir = File::open(tempdir.path()).unwrap();
+
+    let stx = statx(Some(dir.as_raw_fd()), "foo.txt", fcntl::AtFlags::empty(),
+                    STATX_BASIC_STATS | STATX_BTIME).unwrap();
+    let st = stat(&filename).unwrap();
+    assert!(stx.mask().contains(STATX_BASIC_STATS));
+    assert_eq!(stx.size(), Some(5));
+    assert_eq!(stx.ino(), Some(st.st_ino as u64));
+    assert_eq!(stx.dev(), st.st_dev);
+    assert_eq!(stx.file_type(), Some(stat::S_IFREG));
+    assert_eq!(stx.uid(), Some(st.st_uid));
+    assert_eq!(stx.mtime().unwrap().tv_sec(), st.st_mtime);
+    // Not every file system records the creation time
+    if let Some(btime) = stx.btime() {
+        assert!(btime <= stx.ctime().unwrap());
+    }
+
+    assert!(statx(Some(dir.as_raw_fd()), "missing", fcntl::AtFlags::empty(),
+                  STATX_BASIC_STATS).is_err());
+}
+
+#[test]
+fn test_file_stat_ext() {
+    use nix::sys::stat::{FileStatExt, FileType};
+
+    let tempdir = TempDir::new("nix-test_file_stat_ext").unwrap();
+    let filename = tempdir.path().join("foo.txt");
+    let linkname = tempdir.path().join("foolink");
+    let fifoname = tempdir.path().join("foofifo");
+    File::create(&filename).unwrap();
+    symlink("foo.txt", &linkname).unwrap();
+    stat::mknod(&fifoname, stat::S_IFIFO, stat::S_IRWXU, 0).unwrap();
+    stat::fchmodat(None, &filename, stat::S_IRUSR | stat::S_IWUSR,
+                   fcntl::AtFlags::empty()).unwrap();
+
+    let st = stat(&filename).unwrap();
+    assert_eq!(st.file_type(), FileType::Regular);
+    assert_eq!(st.permissions(), stat::S_IRUSR | stat::S_IWUSR);
+    assert_eq!(st.mtime().tv_sec(), st.st_mtime);
+    assert!(st.atime().tv_sec() > 0);
+    assert!(st.ctime() >= st.mtime());
+    assert_eq!(st.dev(), (stat::major(st.st_dev), stat::minor(st.st_dev)));
+
+    assert_eq!(stat(tempdir.path()).unwrap().file_type(), FileType::Dir);
+    assert_eq!(lstat(&linkname).unwrap().file_type(), FileType::Symlink);
+    assert_eq!(stat(&fifoname).unwrap().file_type(), FileType::Fifo);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_file_stat_ext_dev() {
+    use nix::sys::stat::{FileStatExt, FileType};
+
+    // /dev/null is always character device 1, 3 on Linux
+    assert_eq!(stat("/dev/null").unwrap().file_type(), FileType::CharDev(1, 3));
+}
@@ -180,6 +180,40 @@ fn test_getcwd() {
     assert_eq!(getcwd().unwrap(), current_dir().unwrap());
 }
 
+// AT_REMOVEDIR is only available on Linux so far
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_at_functions() {
+    use nix::fcntl::{AtFlags, AT_REMOVEDIR, AT_SYMLINK_NOFOLLOW};
+
+    let tmpdir = TempDir::new("test_at_functions").unwrap();
+    let dir = File::open(tmpdir.path()).unwrap();
+    let dirfd = Some(dir.as_raw_fd());
+
+    mkdirat(dirfd, "subdir", stat::S_IRWXU).unwrap();
+    assert!(tmpdir.path().join("subdir").is_dir());
+
+    File::create(tmpdir.path().join("file")).unwrap();
+    linkat(dirfd, "file", dirfd, "subdir/link", AtFlags::empty()).unwrap();
+    assert_eq!(tmpdir.path().join("file").metadata().unwrap().nlink(), 2);
+
+    symlinkat("file", dirfd, "symlink").unwrap();
+    assert_eq!(tmpdir.path().join("symlink").read_link().unwrap().to_str(), Some("file"));
+
+    faccessat(dirfd, "symlink", F_OK | R_OK | W_OK, AtFlags::empty()).unwrap();
+    assert!(faccessat(dirfd, "missing", F_OK, AtFlags::empty()).is_err());
+
+    // Changing to the current owner and group is always allowed
+    fchownat(dirfd, "symlink", Some(getuid()), Some(getgid()), AT_SYMLINK_NOFOLLOW).unwrap();
+    fchownat(dirfd, "file", None, None, AtFlags::empty()).unwrap();
+
+    unlinkat(dirfd, "symlink", AtFlags::empty()).unwrap();
+    unlinkat(dirfd, "subdir/link", AtFlags::empty()).unwrap();
+    assert!(unlinkat(dirfd, "subdir", AtFlags::empty()).is_err());
+    unlinkat(dirfd, "subdir", AT_REMOVEDIR).unwrap();
+    assert!(!tmpdir.path().join("subdir").exists());
+}
+
 #[test]
 fn test_lseek() {
     const CONTENTS: &'static [u8] = b"abcdef123456";